@@ -0,0 +1,114 @@
+//! Re-downloads the resolver test fixtures in `src/resolvers/fixtures`.
+//!
+//! Usage: `cargo run --bin refresh_fixtures [fixture ...]`
+//!
+//! Without arguments every fixture is refreshed. The parser tests assert on the
+//! exact codes in each snapshot, so update them after refreshing.
+
+use std::path::PathBuf;
+use starrail_api::resolvers::CodeResolver;
+use starrail_api::resolvers::eurogamer::EurogamerResolver;
+use starrail_api::resolvers::fandom::FandomResolver;
+use starrail_api::resolvers::game8::Game8Resolver;
+use starrail_api::resolvers::hoyolab::{self, HoyolabResolver};
+use starrail_api::resolvers::news;
+use starrail_api::resolvers::polygon::PolygonResolver;
+use starrail_api::resolvers::prydwen::PrydwenResolver;
+use starrail_api::utils::http::HttpClient;
+
+// Requests are built from the resolvers' own URLs, queries and headers, so the snapshots
+// match what the scrapers download.
+struct Fixture {
+    file: &'static str,
+    url: String,
+    query: Vec<(&'static str, String)>,
+    hoyolab_headers: bool,
+}
+
+impl Fixture {
+    fn page(file: &'static str, resolver: &dyn CodeResolver) -> Self {
+        Self { file, url: resolver.base_url(), query: Vec::new(), hoyolab_headers: false }
+    }
+
+    fn api(file: &'static str, url: String, query: &[(&'static str, &str)]) -> Self {
+        let query = query.iter().map(|&(name, value)| (name, value.to_string())).collect();
+        Self { file, url, query, hoyolab_headers: true }
+    }
+
+    fn news_list(file: &'static str, news_type: i32) -> Self {
+        let mut fixture = Self::api(file, format!("{}{}", news::DEFAULT_HOST, news::NEWS_LIST_PATH), news::NEWS_LIST_QUERY);
+        fixture.query.push(("type", news_type.to_string()));
+        fixture
+    }
+}
+
+fn fixtures() -> Vec<Fixture> {
+    vec![
+        Fixture::page("game8.html", &Game8Resolver::new()),
+        Fixture::page("prydwen.html", &PrydwenResolver::new()),
+        Fixture::page("fandom.html", &FandomResolver::new()),
+        Fixture::page("polygon.html", &PolygonResolver::new()),
+        Fixture::page("eurogamer.html", &EurogamerResolver::new()),
+        Fixture::api("hoyolab.json", HoyolabResolver::new().base_url(), hoyolab::QUERY),
+        Fixture::api("news_events.json", format!("{}{}", news::DEFAULT_HOST, news::EVENT_LIST_PATH), news::EVENT_LIST_QUERY),
+        Fixture::news_list("news_notices.json", news::NOTICE_TYPE),
+        Fixture::news_list("news_info.json", news::INFO_TYPE),
+    ]
+}
+
+async fn download(client: &HttpClient, fixture: &Fixture) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let mut request = client
+        .get(&fixture.url)
+        .query(&fixture.query);
+
+    if fixture.hoyolab_headers {
+        request = hoyolab::with_api_headers(request).header("x-rpc-language", "en-us");
+    }
+
+    let response = client.send(request).await?;
+    if !response.status().is_success() {
+        return Err(format!("{} returned {}", fixture.url, response.status()).into());
+    }
+
+    let body = response.text().await?;
+    if fixture.file.ends_with(".json") {
+        // Pretty-print so fixture diffs stay reviewable
+        let value: serde_json::Value = serde_json::from_str(&body)?;
+        return Ok(serde_json::to_string_pretty(&value)? + "\n");
+    }
+
+    Ok(body)
+}
+
+#[tokio::main]
+async fn main() {
+    let wanted: Vec<String> = std::env::args().skip(1).collect();
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/resolvers/fixtures");
+    let client = HttpClient::default();
+    let mut failed = false;
+
+    for fixture in fixtures() {
+        let stem = fixture.file.split('.').next().unwrap_or(fixture.file);
+        if !wanted.is_empty() && !wanted.iter().any(|name| name == stem || name == fixture.file) {
+            continue;
+        }
+
+        match download(&client, &fixture).await {
+            Ok(body) => match std::fs::write(dir.join(fixture.file), body) {
+                Ok(()) => println!("Refreshed {}", fixture.file),
+                Err(e) => {
+                    eprintln!("Failed to write {}: {}", fixture.file, e);
+                    failed = true;
+                }
+            },
+            Err(e) => {
+                eprintln!("Failed to download {}: {}", fixture.file, e);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
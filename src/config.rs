@@ -1,6 +1,13 @@
 use config::{Config, ConfigError};
+use log::error;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Duration;
+use crate::resolvers;
+use crate::utils::http::{self, HttpClient, HttpOptions};
+use crate::services::validator_service;
+use crate::services::notification_service::CodeEvent;
 
 #[derive(Debug, Deserialize)]
 #[serde(default)]
@@ -21,20 +28,260 @@ pub struct MongoConfig {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SqliteConfig {
+    #[serde(default = "default_sqlite_path")]
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    #[default]
+    Mongodb,
+    Sqlite,
+    Memory,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct StorageConfig {
+    pub backend: StorageBackend,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
 pub struct HoyolabConfig {
     pub region: String,
     pub uid: String,
     pub cookie: String,
+    #[serde(default)]
+    pub user_agent: String,
+    /// Server the account plays on. Codes are only validated with accounts of their server.
+    #[serde(default)]
+    pub server: resolvers::Server,
+}
+
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountSelection {
+    #[default]
+    RoundRobin,
+    LeastRecentlyUsed,
+}
+
+/// Extra accounts that validate codes alongside `hoyolab`, and how requests are shared out.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ValidatorConfig {
+    pub accounts: Vec<HoyolabConfig>,
+    pub selection: AccountSelection,
+    /// Seconds an account is rested after the redeem endpoint reports a cooldown.
+    #[serde(default = "default_account_cooldown")]
+    pub cooldown: u64,
+    /// Redeem requests per minute, shared by every account. 0 removes the limit.
+    #[serde(default = "default_requests_per_minute")]
+    pub requests_per_minute: u32,
+    /// Seconds after its last validation that an active code is queued for revalidation.
+    #[serde(default = "default_revalidate_after")]
+    pub revalidate_after: u64,
+    /// Seconds before a queued code that hit a cooldown or an unknown result is tried again,
+    /// doubled for every further retry.
+    #[serde(default = "default_validation_retry_delay")]
+    pub retry_delay: u64,
+    /// Cap on the retry delay in seconds.
+    #[serde(default = "default_validation_max_retry_delay")]
+    pub max_retry_delay: u64,
+}
+
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    #[default]
+    Json,
+    Discord,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    #[serde(default = "default_webhook_events")]
+    pub events: Vec<CodeEvent>,
+    #[serde(default = "default_webhook_retries")]
+    pub retries: u32,
+}
+
+/// Time-to-live of cached code and news responses in seconds. 0 disables caching.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct CacheConfig {
+    #[serde(default = "default_cache_ttl")]
+    pub codes_ttl: u64,
+    #[serde(default = "default_cache_ttl")]
+    pub news_ttl: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct HealthConfig {
+    /// Seconds a scheduler job or resolver may keep failing before `/ready` reports it as degraded.
+    #[serde(default = "default_degraded_after")]
+    pub degraded_after: u64,
+}
+
+/// When to stop scraping a resolver that keeps failing or finding nothing. Times are in seconds.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    #[serde(default = "default_empty_threshold")]
+    pub empty_threshold: u32,
+    /// First backoff once the breaker opens, doubled after every failed retry.
+    #[serde(default = "default_breaker_backoff")]
+    pub backoff: u64,
+    #[serde(default = "default_breaker_max_backoff")]
+    pub max_backoff: u64,
+    /// Overrides keyed by the lowercased resolver name (`hoyolab`, `eurogamer`, ...), like
+    /// `http.sources`.
+    pub sources: HashMap<String, BreakerOverrides>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct BreakerOverrides {
+    /// `0` never opens the breaker for empty results, for sources that are often legitimately
+    /// empty, such as Hoyolab between livestreams.
+    pub empty_threshold: Option<u32>,
+}
+
+impl CircuitBreakerConfig {
+    /// Empty scrapes in a row that open the breaker of `source`, or `None` if empty results
+    /// never do.
+    pub fn empty_threshold(&self, source: &str) -> Option<u32> {
+        let threshold = self.sources.get(&source.to_lowercase())
+            .and_then(|overrides| overrides.empty_threshold)
+            .unwrap_or(self.empty_threshold);
+        (threshold > 0).then_some(threshold)
+    }
+}
+
+/// Client settings for requests to the upstream sources. Times are in seconds.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct HttpConfig {
+    #[serde(default = "default_connect_timeout")]
+    pub connect_timeout: u64,
+    /// Limit on a whole request, including reading the body.
+    #[serde(default = "default_request_timeout")]
+    pub timeout: u64,
+    /// Retries for timeouts, connection errors, 429 and 5xx responses.
+    #[serde(default = "default_http_retries")]
+    pub retries: u32,
+    /// First retry delay, doubled for every further retry and jittered.
+    #[serde(default = "default_retry_delay")]
+    pub retry_delay: u64,
+    /// Longest single retry delay, also capping `Retry-After`.
+    #[serde(default = "default_max_retry_delay")]
+    pub max_retry_delay: u64,
+    #[serde(default = "default_user_agent")]
     pub user_agent: String,
+    /// `http://`, `https://` or `socks5://` proxy URL.
+    pub proxy: Option<String>,
+    /// Overrides keyed by source: the `upstream` keys (`game8`, `hoyolab`, `news`, `redeem`, ...)
+    /// or the lowercased name of a config-driven source.
+    pub sources: HashMap<String, HttpOverrides>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct HttpOverrides {
+    pub connect_timeout: Option<u64>,
+    pub timeout: Option<u64>,
+    pub retries: Option<u32>,
+    pub user_agent: Option<String>,
+    pub proxy: Option<String>,
 }
 
+impl HttpConfig {
+    /// The settings for `source`, with its overrides applied.
+    pub fn options(&self, source: &str) -> HttpOptions {
+        let overrides = self.sources.get(&source.to_lowercase()).cloned().unwrap_or_default();
+        HttpOptions {
+            connect_timeout: Duration::from_secs(overrides.connect_timeout.unwrap_or(self.connect_timeout)),
+            timeout: Duration::from_secs(overrides.timeout.unwrap_or(self.timeout)),
+            retries: overrides.retries.unwrap_or(self.retries),
+            retry_delay: Duration::from_secs(self.retry_delay),
+            max_retry_delay: Duration::from_secs(self.max_retry_delay),
+            user_agent: overrides.user_agent.unwrap_or_else(|| self.user_agent.clone()),
+            proxy: overrides.proxy.or_else(|| self.proxy.clone()),
+        }
+    }
+
+    /// A client for `source`. [`Settings::new`] rejects invalid proxies, so falling back to the
+    /// defaults only happens for settings built by hand.
+    pub fn client(&self, source: &str) -> HttpClient {
+        HttpClient::new(self.options(source)).unwrap_or_else(|e| {
+            error!("Invalid HTTP settings for {}, using defaults: {}", source, e);
+            HttpClient::default()
+        })
+    }
+}
+
+/// Upstream endpoints, overridable so the scrapers and validator can run against a stub server.
 #[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct UpstreamConfig {
+    #[serde(default = "default_game8_url")]
+    pub game8: String,
+    #[serde(default = "default_prydwen_url")]
+    pub prydwen: String,
+    #[serde(default = "default_fandom_url")]
+    pub fandom: String,
+    #[serde(default = "default_polygon_url")]
+    pub polygon: String,
+    #[serde(default = "default_eurogamer_url")]
+    pub eurogamer: String,
+    #[serde(default = "default_hoyolab_url")]
+    pub hoyolab: String,
+    #[serde(default = "default_redeem_url")]
+    pub redeem: String,
+    /// Redeem endpoint for China-server codes.
+    #[serde(default = "default_cn_redeem_url")]
+    pub redeem_cn: String,
+    #[serde(default = "default_news_host")]
+    pub news: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
 pub struct Settings {
     #[serde(default)]
     pub server: ServerConfig,
     #[serde(default)]
     pub mongodb: MongoConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub sqlite: SqliteConfig,
     pub hoyolab: HoyolabConfig,
+    #[serde(default)]
+    pub upstream: UpstreamConfig,
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub health: HealthConfig,
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    #[serde(default)]
+    pub validator: ValidatorConfig,
+    #[serde(default)]
+    pub http: HttpConfig,
+    /// Extra code sites scraped by the generic HTML resolver.
+    #[serde(default)]
+    pub sources: Vec<resolvers::html::SiteDefinition>,
 }
 
 impl Settings {
@@ -64,6 +311,19 @@ impl Settings {
         if settings.hoyolab.cookie.is_empty() {
             return Err(ConfigError::Message("Hoyolab cookie is required".into()));
         }
+        for account in &settings.validator.accounts {
+            if account.region.is_empty() || account.uid.is_empty() || account.cookie.is_empty() {
+                return Err(ConfigError::Message("Validator accounts need a region, UID and cookie".into()));
+            }
+        }
+        for source in settings.http.sources.keys().map(String::as_str).chain(["default"]) {
+            HttpClient::new(settings.http.options(source))
+                .map_err(|e| ConfigError::Message(format!("Invalid HTTP settings for {}: {}", source, e)))?;
+        }
+        for definition in &settings.sources {
+            resolvers::html::HtmlResolver::new(definition.clone())
+                .map_err(|e| ConfigError::Message(format!("Invalid source definition: {}", e)))?;
+        }
 
         Ok(settings)
     }
@@ -87,6 +347,88 @@ impl Default for MongoConfig {
     }
 }
 
+impl Default for SqliteConfig {
+    fn default() -> Self {
+        Self {
+            path: default_sqlite_path(),
+        }
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            codes_ttl: default_cache_ttl(),
+            news_ttl: default_cache_ttl(),
+        }
+    }
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            degraded_after: default_degraded_after(),
+        }
+    }
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_failure_threshold(),
+            empty_threshold: default_empty_threshold(),
+            backoff: default_breaker_backoff(),
+            max_backoff: default_breaker_max_backoff(),
+            sources: HashMap::new(),
+        }
+    }
+}
+
+impl Default for ValidatorConfig {
+    fn default() -> Self {
+        Self {
+            accounts: Vec::new(),
+            selection: AccountSelection::default(),
+            cooldown: default_account_cooldown(),
+            requests_per_minute: default_requests_per_minute(),
+            revalidate_after: default_revalidate_after(),
+            retry_delay: default_validation_retry_delay(),
+            max_retry_delay: default_validation_max_retry_delay(),
+        }
+    }
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: default_connect_timeout(),
+            timeout: default_request_timeout(),
+            retries: default_http_retries(),
+            retry_delay: default_retry_delay(),
+            max_retry_delay: default_max_retry_delay(),
+            user_agent: default_user_agent(),
+            proxy: None,
+            sources: HashMap::new(),
+        }
+    }
+}
+
+impl Default for UpstreamConfig {
+    fn default() -> Self {
+        Self {
+            game8: default_game8_url(),
+            prydwen: default_prydwen_url(),
+            fandom: default_fandom_url(),
+            polygon: default_polygon_url(),
+            eurogamer: default_eurogamer_url(),
+            hoyolab: default_hoyolab_url(),
+            redeem: default_redeem_url(),
+            redeem_cn: default_cn_redeem_url(),
+            news: default_news_host(),
+        }
+    }
+}
+
 fn default_host() -> String {
     "0.0.0.0".to_string()
 }
@@ -101,4 +443,120 @@ fn default_mongo_uri() -> String {
 
 fn default_mongo_database() -> String {
     "starrail-api".to_string()
-} 
\ No newline at end of file
+}
+
+fn default_sqlite_path() -> String {
+    "starrail-api.db".to_string()
+}
+
+fn default_game8_url() -> String {
+    resolvers::game8::DEFAULT_BASE_URL.to_string()
+}
+
+fn default_prydwen_url() -> String {
+    resolvers::prydwen::DEFAULT_BASE_URL.to_string()
+}
+
+fn default_fandom_url() -> String {
+    resolvers::fandom::DEFAULT_BASE_URL.to_string()
+}
+
+fn default_polygon_url() -> String {
+    resolvers::polygon::DEFAULT_BASE_URL.to_string()
+}
+
+fn default_eurogamer_url() -> String {
+    resolvers::eurogamer::DEFAULT_BASE_URL.to_string()
+}
+
+fn default_hoyolab_url() -> String {
+    resolvers::hoyolab::DEFAULT_BASE_URL.to_string()
+}
+
+fn default_redeem_url() -> String {
+    validator_service::DEFAULT_REDEEM_URL.to_string()
+}
+
+fn default_cn_redeem_url() -> String {
+    validator_service::DEFAULT_CN_REDEEM_URL.to_string()
+}
+
+fn default_news_host() -> String {
+    resolvers::news::DEFAULT_HOST.to_string()
+}
+
+fn default_webhook_events() -> Vec<CodeEvent> {
+    vec![CodeEvent::Discovered, CodeEvent::Validated, CodeEvent::Deactivated, CodeEvent::SourceDrift]
+}
+
+fn default_webhook_retries() -> u32 {
+    3
+}
+
+fn default_cache_ttl() -> u64 {
+    300
+}
+
+fn default_degraded_after() -> u64 {
+    3600
+}
+
+fn default_failure_threshold() -> u32 {
+    5
+}
+
+fn default_empty_threshold() -> u32 {
+    15
+}
+
+fn default_breaker_backoff() -> u64 {
+    300
+}
+
+fn default_breaker_max_backoff() -> u64 {
+    21600
+}
+
+fn default_account_cooldown() -> u64 {
+    300
+}
+
+fn default_requests_per_minute() -> u32 {
+    6
+}
+
+fn default_revalidate_after() -> u64 {
+    1800
+}
+
+fn default_validation_retry_delay() -> u64 {
+    60
+}
+
+fn default_validation_max_retry_delay() -> u64 {
+    3600
+}
+
+fn default_connect_timeout() -> u64 {
+    10
+}
+
+fn default_request_timeout() -> u64 {
+    30
+}
+
+fn default_http_retries() -> u32 {
+    2
+}
+
+fn default_retry_delay() -> u64 {
+    1
+}
+
+fn default_max_retry_delay() -> u64 {
+    60
+}
+
+fn default_user_agent() -> String {
+    http::DEFAULT_USER_AGENT.to_string()
+}
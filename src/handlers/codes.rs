@@ -1,12 +1,78 @@
-use actix_web::{HttpResponse, Responder};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use serde::{Serialize, Deserialize};
 use log::{debug, error};
-use crate::services::db_service::DbService;
+use mongodb::bson::DateTime;
+use crate::resolvers::{RedemptionCode, Server};
+use crate::rewards::{self, Reward, locale};
+use crate::services::data_version::Dataset;
+use crate::services::storage;
+use crate::utils::lang_parser::parse_language_code;
+use super::conditional::Conditional;
+
+#[derive(Deserialize)]
+pub struct CodeQueryParams {
+    pub lang: Option<String>,
+    /// Only codes of this server. Global codes when unset.
+    pub server: Option<Server>,
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct SimpleRedemptionCode {
     pub code: String,
     pub rewards: Vec<String>,
+    pub items: Vec<Reward>,
+    pub first_seen_at: Option<i64>,
+    pub last_seen_in_source_at: Option<i64>,
+    pub last_validated_at: Option<i64>,
+    pub deactivated_at: Option<i64>,
+    pub server: Server,
+    pub regions: Vec<SimpleRegionStatus>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SimpleRegionStatus {
+    pub region: String,
+    pub valid: bool,
+    pub checked_at: i64,
+}
+
+fn unix_seconds(date: DateTime) -> i64 {
+    date.timestamp_millis() / 1000
+}
+
+impl SimpleRedemptionCode {
+    pub(crate) fn localized(code: RedemptionCode, lang: &str) -> Self {
+        // Codes stored before structured rewards existed only have the raw strings
+        let mut items = if code.items.is_empty() {
+            rewards::parse_rewards(&code.rewards)
+        } else {
+            code.items
+        };
+
+        for item in &mut items {
+            if let Some(name) = item.item_id.as_deref().and_then(|id| locale::localized_name(id, lang)) {
+                item.item_name = name.to_string();
+            }
+        }
+
+        Self {
+            code: code.code,
+            rewards: code.rewards,
+            items,
+            first_seen_at: code.first_seen_at.or(code.date).map(unix_seconds),
+            last_seen_in_source_at: code.last_seen_in_source_at.map(unix_seconds),
+            last_validated_at: code.last_validated_at.map(unix_seconds),
+            deactivated_at: code.deactivated_at.map(unix_seconds),
+            server: code.server,
+            regions: code.regions.into_iter()
+                .map(|status| SimpleRegionStatus {
+                    region: status.region,
+                    valid: status.valid,
+                    checked_at: unix_seconds(status.checked_at),
+                })
+                .collect(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -15,28 +81,30 @@ pub struct SimpleCodeResponse {
     pub inactive: Vec<SimpleRedemptionCode>,
 }
 
-pub async fn get_codes() -> impl Responder {
+pub async fn get_codes(req: HttpRequest, query: web::Query<CodeQueryParams>) -> impl Responder {
     debug!("Handling request to get redemption codes");
+    let lang = query.lang.as_deref().map_or("en-us", parse_language_code);
+    let server = query.server.unwrap_or_default();
+    let conditional = match Conditional::check(&req, Dataset::Codes, format!("codes:{}:{}", lang, server.as_str())) {
+        Ok(conditional) => conditional,
+        Err(hit) => return hit.response(),
+    };
     
-    let db_service = DbService::instance().await;
-    match db_service.get_codes().await {
+    let storage = storage::instance().await;
+    match storage.get_codes().await {
         Ok((active, inactive)) => {
             debug!("Returning {} active and {} inactive codes", active.len(), inactive.len());
             let response = SimpleCodeResponse {
                 active: active.into_iter()
-                    .map(|code| SimpleRedemptionCode {
-                        code: code.code,
-                        rewards: code.rewards,
-                    })
+                    .filter(|code| code.server == server)
+                    .map(|code| SimpleRedemptionCode::localized(code, lang))
                     .collect(),
                 inactive: inactive.into_iter()
-                    .map(|code| SimpleRedemptionCode {
-                        code: code.code,
-                        rewards: code.rewards,
-                    })
+                    .filter(|code| code.server == server)
+                    .map(|code| SimpleRedemptionCode::localized(code, lang))
                     .collect(),
             };
-            HttpResponse::Ok().json(response)
+            conditional.json(&req, &response)
         },
         Err(e) => {
             error!("Failed to get codes from database: {}", e);
@@ -45,4 +113,135 @@ pub async fn get_codes() -> impl Responder {
             }))
         }
     }
-} 
\ No newline at end of file
+} 
+
+#[derive(Serialize)]
+pub struct CodeSourceResponse {
+    pub name: String,
+    pub first_seen_at: i64,
+    pub last_seen_at: i64,
+    pub rewards: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct CodeDetailResponse {
+    #[serde(flatten)]
+    pub code: SimpleRedemptionCode,
+    pub active: bool,
+    pub sources: Vec<CodeSourceResponse>,
+}
+
+pub async fn get_code_detail(req: HttpRequest, path: web::Path<String>) -> impl Responder {
+    let code = path.into_inner();
+    debug!("Handling request to get details for code {}", code);
+    let conditional = match Conditional::check(&req, Dataset::Codes, format!("code:{}", code)) {
+        Ok(conditional) => conditional,
+        Err(hit) => return hit.response(),
+    };
+
+    let storage = storage::instance().await;
+    match storage.get_code(&code).await {
+        Ok(Some(code)) => {
+            let mut sources: Vec<CodeSourceResponse> = code.sources.iter()
+                .map(|source| CodeSourceResponse {
+                    name: source.name.clone(),
+                    first_seen_at: unix_seconds(source.first_seen_at),
+                    last_seen_at: unix_seconds(source.last_seen_at),
+                    rewards: source.rewards.clone(),
+                })
+                .collect();
+            sources.sort_by_key(|source| source.first_seen_at);
+
+            let active = code.active;
+            conditional.json(&req, &CodeDetailResponse {
+                code: SimpleRedemptionCode::localized(code, "en-us"),
+                active,
+                sources,
+            })
+        },
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Code not found"
+            }))
+        },
+        Err(e) => {
+            error!("Failed to get code {} from database: {}", code, e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch code from database"
+            }))
+        }
+    }
+}
+
+const DEFAULT_VALIDATIONS_LIMIT: usize = 100;
+const MAX_VALIDATIONS_LIMIT: usize = 1000;
+
+#[derive(Deserialize)]
+pub struct ValidationQueryParams {
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct ValidationAttemptResponse {
+    pub validated_at: i64,
+    pub uid: String,
+    /// Unknown for attempts made before per-region validation.
+    pub region: Option<String>,
+    pub retcode: Option<i32>,
+    pub message: String,
+    pub result: String,
+}
+
+#[derive(Serialize)]
+pub struct ValidationTimelineResponse {
+    pub code: String,
+    pub validations: Vec<ValidationAttemptResponse>,
+}
+
+/// Not cached or made conditional: attempts that do not change a code's status, such as
+/// cooldowns, are recorded without bumping the codes data version.
+pub async fn get_code_validations(path: web::Path<String>, query: web::Query<ValidationQueryParams>) -> impl Responder {
+    let code = path.into_inner();
+    let limit = query.limit.unwrap_or(DEFAULT_VALIDATIONS_LIMIT).clamp(1, MAX_VALIDATIONS_LIMIT);
+    debug!("Handling request to get the validation timeline of code {}", code);
+
+    let storage = storage::instance().await;
+    match storage.get_code(&code).await {
+        Ok(Some(_)) => {},
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Code not found"
+            }));
+        },
+        Err(e) => {
+            error!("Failed to get code {} from database: {}", code, e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch code from database"
+            }));
+        }
+    }
+
+    match storage.get_validations(&code, limit).await {
+        Ok(attempts) => {
+            // Stored newest first; the timeline reads oldest first
+            let validations = attempts.into_iter()
+                .rev()
+                .map(|attempt| ValidationAttemptResponse {
+                    validated_at: unix_seconds(attempt.validated_at),
+                    uid: attempt.uid,
+                    region: Some(attempt.region).filter(|region| !region.is_empty()),
+                    retcode: attempt.retcode,
+                    message: attempt.message,
+                    result: attempt.result,
+                })
+                .collect();
+            HttpResponse::Ok().json(ValidationTimelineResponse { code, validations })
+        },
+        Err(e) => {
+            error!("Failed to get validations of code {} from database: {}", code, e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch validations from database"
+            }))
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse};
+use actix_web::http::header::{self, EntityTag, HttpDate, IfModifiedSince, IfNoneMatch};
+use actix_web::web::Bytes;
+use log::debug;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::services::data_version::{self, Dataset, Version};
+use crate::services::response_cache::{self, CachedResponse};
+
+/// A read endpoint whose response only changes when `dataset` does.
+/// `key` must identify the response, including every query parameter that affects it.
+pub struct Conditional {
+    dataset: Dataset,
+    key: String,
+    version: Version,
+}
+
+impl Conditional {
+    /// Returns `Err` with the cached response when it is still current, so the handler
+    /// can skip storage entirely.
+    pub fn check(req: &HttpRequest, dataset: Dataset, key: String) -> Result<Self, CacheHit> {
+        let version = data_version::current(dataset);
+
+        if let Some(cached) = response_cache::instance().get(dataset, &key, version) {
+            debug!("Serving {} from the response cache", key);
+            let not_modified = is_fresh(req, &EntityTag::new_strong(cached.etag.clone()), cached.modified_at);
+            return Err(CacheHit { cached, not_modified });
+        }
+
+        Ok(Self { dataset, key, version })
+    }
+
+    pub fn json<T: Serialize>(self, req: &HttpRequest, body: &T) -> HttpResponse {
+        match serde_json::to_vec(body) {
+            Ok(body) => self.respond(req, "application/json", body),
+            Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": format!("Failed to serialize response: {}", e)
+            })),
+        }
+    }
+
+    pub fn respond(self, req: &HttpRequest, content_type: &str, body: Vec<u8>) -> HttpResponse {
+        let etag = format!("{:x}", Sha256::digest(&body));
+        let cached = CachedResponse::new(self.version, etag, content_type, Bytes::from(body));
+        let not_modified = is_fresh(req, &EntityTag::new_strong(cached.etag.clone()), cached.modified_at);
+        let response = build_response(&cached, not_modified, "MISS");
+
+        response_cache::instance().insert(self.dataset, self.key, cached);
+        response
+    }
+}
+
+pub struct CacheHit {
+    cached: Arc<CachedResponse>,
+    not_modified: bool,
+}
+
+impl CacheHit {
+    pub fn response(self) -> HttpResponse {
+        build_response(&self.cached, self.not_modified, "HIT")
+    }
+}
+
+fn build_response(cached: &CachedResponse, not_modified: bool, cache_status: &str) -> HttpResponse {
+    let mut response = if not_modified {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    response
+        .insert_header(header::ETag(EntityTag::new_strong(cached.etag.clone())))
+        .insert_header(header::LastModified(http_date(cached.modified_at)))
+        .insert_header(("X-Cache", cache_status));
+
+    if not_modified {
+        response.finish()
+    } else {
+        response.content_type(cached.content_type.as_str()).body(cached.body.clone())
+    }
+}
+
+fn http_date(seconds: i64) -> HttpDate {
+    HttpDate::from(UNIX_EPOCH + Duration::from_secs(seconds.max(0) as u64))
+}
+
+/// `If-None-Match` takes precedence; `If-Modified-Since` is only used without it.
+fn is_fresh(req: &HttpRequest, etag: &EntityTag, modified_at: i64) -> bool {
+    if let Some(if_none_match) = req.get_header::<IfNoneMatch>() {
+        return match if_none_match {
+            IfNoneMatch::Any => true,
+            IfNoneMatch::Items(tags) => tags.iter().any(|tag| tag.weak_eq(etag)),
+        };
+    }
+    if req.headers().contains_key(header::IF_NONE_MATCH) {
+        return false;
+    }
+
+    match req.get_header::<IfModifiedSince>() {
+        Some(IfModifiedSince(since)) => SystemTime::from(since) >= SystemTime::from(http_date(modified_at)),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+
+    fn etag_of(response: &HttpResponse) -> String {
+        response.headers().get(header::ETAG).unwrap().to_str().unwrap().to_string()
+    }
+
+    fn get(req: &HttpRequest, key: &str, body: &serde_json::Value) -> HttpResponse {
+        match Conditional::check(req, Dataset::Codes, key.to_string()) {
+            Err(hit) => hit.response(),
+            // Also taken when a concurrent test bumped the version
+            Ok(conditional) => conditional.json(req, body),
+        }
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let key = "test:if-none-match";
+        let body = serde_json::json!({ "active": ["STARRAILGIFT"] });
+
+        let req = TestRequest::default().to_http_request();
+        let response = get(&req, key, &body);
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = etag_of(&response);
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, etag.clone()))
+            .to_http_request();
+        let response = get(&req, key, &body);
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(etag_of(&response), etag);
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, "\"stale\""))
+            .to_http_request();
+        let response = get(&req, key, &body);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(etag_of(&response), etag);
+    }
+
+    #[test]
+    fn test_if_modified_since() {
+        let etag = EntityTag::new_strong("abc".to_string());
+        let modified_at = 1_760_745_600;
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_MODIFIED_SINCE, "Sat, 18 Oct 2025 00:00:00 GMT"))
+            .to_http_request();
+        assert!(is_fresh(&req, &etag, modified_at));
+        assert!(!is_fresh(&req, &etag, modified_at + 1));
+
+        // If-None-Match wins over If-Modified-Since
+        let req = TestRequest::default()
+            .insert_header((header::IF_MODIFIED_SINCE, "Sat, 18 Oct 2025 00:00:00 GMT"))
+            .insert_header((header::IF_NONE_MATCH, "\"other\""))
+            .to_http_request();
+        assert!(!is_fresh(&req, &etag, modified_at));
+    }
+}
@@ -13,9 +13,21 @@ pub async fn get_api_endpoints() -> impl Responder {
         endpoints: vec![
             String::from("/starrail"),
             String::from("/starrail/code"),
+            String::from("/starrail/code/{code}"),
+            String::from("/starrail/code/{code}/validations"),
+            String::from("/starrail/code/feed.xml"),
+            String::from("/starrail/code/atom.xml"),
             String::from("/starrail/news/events"),
             String::from("/starrail/news/notices"),
             String::from("/starrail/news/info"),
+            String::from("/starrail/news/{type}/feed.xml"),
+            String::from("/starrail/news/{type}/atom.xml"),
+            String::from("/starrail/sources"),
+            String::from("/starrail/stream"),
+            String::from("/starrail/stream/ws"),
+            String::from("/metrics"),
+            String::from("/health"),
+            String::from("/ready"),
         ],
     };
     info!("Returning list of API endpoints");
@@ -0,0 +1,244 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use log::{debug, error};
+use crate::resolvers::RedemptionCode;
+use crate::resolvers::news::NewsItem;
+use crate::services::data_version::Dataset;
+use crate::services::news_service::NewsService;
+use crate::services::storage;
+use crate::utils::feed::{Feed, FeedEntry};
+use crate::utils::lang_parser::parse_language_code;
+use super::codes::{CodeQueryParams, SimpleRedemptionCode};
+use super::conditional::Conditional;
+use super::news::NewsQueryParams;
+
+const CODE_FEED_LIMIT: usize = 50;
+
+#[derive(Clone, Copy)]
+enum FeedFormat {
+    Rss,
+    Atom,
+}
+
+fn feed_response(req: &HttpRequest, conditional: Conditional, feed: Feed, format: FeedFormat) -> HttpResponse {
+    match format {
+        FeedFormat::Rss => conditional.respond(req, "application/rss+xml; charset=utf-8", feed.to_rss().into_bytes()),
+        FeedFormat::Atom => conditional.respond(req, "application/atom+xml; charset=utf-8", feed.to_atom().into_bytes()),
+    }
+}
+
+fn code_entry(code: RedemptionCode, lang: &str) -> FeedEntry {
+    let active = code.active;
+    let code = SimpleRedemptionCode::localized(code, lang);
+    let published = code.first_seen_at.unwrap_or(0);
+
+    FeedEntry {
+        id: format!("urn:starrail-api:code:{}", code.code),
+        title: if active { code.code.clone() } else { format!("{} (expired)", code.code) },
+        link: format!("https://hsr.hoyoverse.com/gift?code={}", code.code),
+        summary: reward_summary(&code, lang),
+        published,
+        updated: code.deactivated_at.unwrap_or(published),
+    }
+}
+
+/// The source wording in English, or the translated items in other languages.
+fn reward_summary(code: &SimpleRedemptionCode, lang: &str) -> String {
+    if lang == "en-us" || code.items.is_empty() {
+        return code.rewards.join(", ");
+    }
+
+    code.items.iter()
+        .map(|item| match item.amount {
+            Some(amount) => format!("{} x{}", item.item_name, amount),
+            None => item.item_name.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Feed entries for the latest news of `news_type` in `lang`, a full language code.
+async fn news_entries(news_service: &NewsService, news_type: &str, lang: &str) -> Result<Vec<FeedEntry>, Box<dyn std::error::Error + Send + Sync>> {
+    let news = news_service.get_news(Some(news_type), lang, None).await?;
+    Ok(news.into_iter().map(news_entry).collect())
+}
+
+fn news_entry(item: NewsItem) -> FeedEntry {
+    FeedEntry {
+        id: format!("urn:starrail-api:news:{}", item.external_id),
+        title: item.title,
+        link: item.url,
+        summary: item.description,
+        published: item.created_at,
+        updated: item.created_at,
+    }
+}
+
+async fn code_feed(req: HttpRequest, query: web::Query<CodeQueryParams>, format: FeedFormat) -> HttpResponse {
+    debug!("Handling request for the code feed");
+    let lang = query.lang.as_deref().map_or("en-us", parse_language_code);
+    // The feed links to itself, so the full URL is part of the response
+    let conditional = match Conditional::check(&req, Dataset::Codes, req.full_url().to_string()) {
+        Ok(conditional) => conditional,
+        Err(hit) => return hit.response(),
+    };
+
+    let storage = storage::instance().await;
+    match storage.get_codes().await {
+        Ok((active, inactive)) => {
+            let server = query.server.unwrap_or_default();
+            let mut entries: Vec<FeedEntry> = active.into_iter()
+                .chain(inactive)
+                .filter(|code| code.server == server)
+                .map(|code| code_entry(code, lang))
+                .collect();
+            entries.sort_by_key(|entry| std::cmp::Reverse(entry.published));
+            entries.truncate(CODE_FEED_LIMIT);
+
+            feed_response(&req, conditional, Feed {
+                title: "Honkai: Star Rail Redemption Codes".to_string(),
+                description: "New and expired Honkai: Star Rail redemption codes".to_string(),
+                link: "https://hsr.hoyoverse.com/gift".to_string(),
+                self_url: req.full_url().to_string(),
+                lang: lang.to_string(),
+                entries,
+            }, format)
+        },
+        Err(e) => {
+            error!("Failed to get codes from database: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch codes from database"
+            }))
+        }
+    }
+}
+
+async fn news_feed(req: HttpRequest, path: web::Path<String>, query: web::Query<NewsQueryParams>, format: FeedFormat) -> HttpResponse {
+    let (news_type, title) = match path.as_str() {
+        "events" => ("event", "Events"),
+        "notices" => ("notice", "Notices"),
+        "info" => ("info", "Info"),
+        _ => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Unknown news type"
+            }));
+        }
+    };
+    debug!("Handling request for the {} news feed", news_type);
+    let lang = query.lang.as_deref().map_or("en-us", parse_language_code);
+    let conditional = match Conditional::check(&req, Dataset::News, req.full_url().to_string()) {
+        Ok(conditional) => conditional,
+        Err(hit) => return hit.response(),
+    };
+
+    let entries = match NewsService::new().await {
+        Ok(news_service) => news_entries(&news_service, news_type, lang).await,
+        Err(e) => Err(e),
+    };
+
+    match entries {
+        Ok(entries) => feed_response(&req, conditional, Feed {
+            title: format!("Honkai: Star Rail News: {}", title),
+            description: format!("Honkai: Star Rail {} from HoYoLAB", title.to_lowercase()),
+            link: "https://www.hoyolab.com/circles/6/39/official".to_string(),
+            self_url: req.full_url().to_string(),
+            lang: lang.to_string(),
+            entries,
+        }, format),
+        Err(e) => {
+            error!("Failed to fetch {} news: {}", news_type, e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": format!("Failed to fetch {} news", news_type)
+            }))
+        }
+    }
+}
+
+pub async fn get_code_rss(req: HttpRequest, query: web::Query<CodeQueryParams>) -> impl Responder {
+    code_feed(req, query, FeedFormat::Rss).await
+}
+
+pub async fn get_code_atom(req: HttpRequest, query: web::Query<CodeQueryParams>) -> impl Responder {
+    code_feed(req, query, FeedFormat::Atom).await
+}
+
+pub async fn get_news_rss(req: HttpRequest, path: web::Path<String>, query: web::Query<NewsQueryParams>) -> impl Responder {
+    news_feed(req, path, query, FeedFormat::Rss).await
+}
+
+pub async fn get_news_atom(req: HttpRequest, path: web::Path<String>, query: web::Query<NewsQueryParams>) -> impl Responder {
+    news_feed(req, path, query, FeedFormat::Atom).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Settings;
+    use crate::resolvers::Server;
+    use crate::services::storage::memory::MemoryStorage;
+    use crate::services::storage::NewsRepository;
+    use mongodb::bson::DateTime;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_code_entry_timestamps() {
+        let first_seen = DateTime::from_millis(1_682_467_200_000);
+        let deactivated = DateTime::from_millis(1_760_745_600_000);
+        let code = RedemptionCode {
+            id: None,
+            code: "STARRAILGIFT".to_string(),
+            rewards: vec!["50 Stellar Jade".to_string(), "10000 Credit".to_string()],
+            items: Vec::new(),
+            source: "Game8".to_string(),
+            sources: Vec::new(),
+            active: false,
+            date: Some(first_seen),
+            first_seen_at: Some(first_seen),
+            last_seen_in_source_at: Some(deactivated),
+            last_validated_at: Some(deactivated),
+            deactivated_at: Some(deactivated),
+            server: Server::Global,
+            regions: Vec::new(),
+        };
+
+        let entry = code_entry(code.clone(), "en-us");
+        assert_eq!(entry.id, "urn:starrail-api:code:STARRAILGIFT");
+        assert_eq!(entry.title, "STARRAILGIFT (expired)");
+        assert_eq!(entry.summary, "50 Stellar Jade, 10000 Credit");
+
+        // Other languages list the translated items instead of the source wording
+        let entry = code_entry(code, "ja-jp");
+        assert_eq!(entry.summary, "星玉 x50, 信用ポイント x10000");
+        assert_eq!(entry.published, 1_682_467_200);
+        assert_eq!(entry.updated, 1_760_745_600);
+    }
+
+    fn notice(id: &str, title: &str, lang: &str) -> NewsItem {
+        NewsItem {
+            id: None,
+            external_id: id.to_string(),
+            title: title.to_string(),
+            description: String::new(),
+            created_at: 1_760_745_600,
+            banner: None,
+            url: format!("https://www.hoyolab.com/article/{}", id),
+            news_type: "notice".to_string(),
+            lang: lang.to_string(),
+            start_at: None,
+            end_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_news_entries_follow_lang() {
+        let storage = Arc::new(MemoryStorage::new());
+        storage.save_news(&[
+            notice("1", "Version 3.7 Update Notice", "en-us"),
+            notice("2", "Ver.3.7アップデートのお知らせ", "ja-jp"),
+        ]).await.unwrap();
+        let news_service = NewsService::with_storage(&Settings::default(), storage);
+
+        let entries = news_entries(&news_service, "notice", parse_language_code("ja")).await.unwrap();
+        let titles: Vec<&str> = entries.iter().map(|entry| entry.title.as_str()).collect();
+        assert_eq!(titles, vec!["Ver.3.7アップデートのお知らせ"]);
+    }
+}
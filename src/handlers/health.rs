@@ -0,0 +1,90 @@
+use actix_web::{HttpResponse, Responder};
+use serde::Serialize;
+use log::{debug, error};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use crate::services::health::{self, Component, ComponentHealth};
+use crate::services::storage;
+use crate::services::validator_pool::{self, AccountStatus};
+
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize)]
+pub struct StorageStatus {
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ComponentStatus {
+    pub status: &'static str,
+    #[serde(flatten)]
+    pub health: ComponentHealth,
+}
+
+#[derive(Serialize)]
+pub struct ReadinessResponse {
+    pub status: &'static str,
+    pub storage: StorageStatus,
+    pub jobs: BTreeMap<String, ComponentStatus>,
+    pub resolvers: BTreeMap<String, ComponentStatus>,
+    pub accounts: Vec<AccountStatus>,
+}
+
+/// Liveness: the process is up and serving requests.
+pub async fn get_health() -> impl Responder {
+    HttpResponse::Ok().json(serde_json::json!({ "status": "ok" }))
+}
+
+fn component_statuses(component: Component, now: i64, degraded: &mut bool) -> BTreeMap<String, ComponentStatus> {
+    let registry = health::instance();
+    registry.snapshot(component)
+        .into_iter()
+        .map(|(name, health)| {
+            let is_degraded = health.is_degraded(now, registry.degraded_after);
+            *degraded |= is_degraded;
+            (name, ComponentStatus { status: if is_degraded { "degraded" } else { "ok" }, health })
+        })
+        .collect()
+}
+
+/// Readiness: storage must answer a ping. Jobs and resolvers that keep failing, and quarantined
+/// validator accounts, only degrade the status, since the API can still serve what is already stored.
+pub async fn get_ready() -> impl Responder {
+    debug!("Handling readiness check");
+    let storage = storage::instance().await;
+    let storage_status = match tokio::time::timeout(PING_TIMEOUT, storage.ping()).await {
+        Ok(Ok(())) => StorageStatus { status: "ok", error: None },
+        Ok(Err(e)) => {
+            error!("Storage ping failed: {}", e);
+            StorageStatus { status: "error", error: Some(e.to_string()) }
+        },
+        Err(_) => {
+            error!("Storage ping timed out");
+            StorageStatus { status: "error", error: Some("Ping timed out".to_string()) }
+        },
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    let mut degraded = false;
+    let jobs = component_statuses(Component::Job, now, &mut degraded);
+    let resolvers = component_statuses(Component::Resolver, now, &mut degraded);
+    let accounts = validator_pool::instance().snapshot();
+    degraded |= accounts.iter().any(|account| account.quarantined);
+
+    let storage_ok = storage_status.error.is_none();
+    let response = ReadinessResponse {
+        status: if !storage_ok { "unavailable" } else if degraded { "degraded" } else { "ok" },
+        storage: storage_status,
+        jobs,
+        resolvers,
+        accounts,
+    };
+
+    if storage_ok {
+        HttpResponse::Ok().json(response)
+    } else {
+        HttpResponse::ServiceUnavailable().json(response)
+    }
+}
@@ -0,0 +1,15 @@
+use actix_web::{HttpResponse, Responder};
+use log::error;
+use crate::services::metrics;
+
+pub async fn get_metrics() -> impl Responder {
+    match metrics::instance().render() {
+        Ok(body) => HttpResponse::Ok()
+            .content_type("text/plain; version=0.0.4; charset=utf-8")
+            .body(body),
+        Err(e) => {
+            error!("Failed to render metrics: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
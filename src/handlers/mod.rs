@@ -1,3 +1,9 @@
 pub mod news;
 pub mod codes;
-pub mod endpoints; 
\ No newline at end of file
+pub mod endpoints;
+pub mod conditional;
+pub mod feeds;
+pub mod stream;
+pub mod metrics;
+pub mod health;
+pub mod sources;
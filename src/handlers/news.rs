@@ -1,23 +1,36 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use serde::Deserialize;
 use log::{debug, error};
-use crate::services::news_service::NewsService;
+use crate::services::data_version::Dataset;
+use crate::services::news_service::{NewsService, EventStatus};
+use crate::utils::lang_parser::parse_language_code;
+use super::conditional::Conditional;
 
 #[derive(Deserialize)]
 pub struct NewsQueryParams {
     pub lang: Option<String>,
 }
 
-pub async fn get_news_events(query: web::Query<NewsQueryParams>) -> impl Responder {
+#[derive(Deserialize)]
+pub struct EventQueryParams {
+    pub lang: Option<String>,
+    pub status: Option<EventStatus>,
+}
+
+pub async fn get_news_events(req: HttpRequest, query: web::Query<EventQueryParams>) -> impl Responder {
     debug!("Handling request to get news events");
-    let lang = query.lang.as_deref();
+    let lang = query.lang.as_deref().map_or("en-us", parse_language_code);
+    let conditional = match Conditional::check(&req, Dataset::News, format!("news:event:{}:{:?}", lang, query.status)) {
+        Ok(conditional) => conditional,
+        Err(hit) => return hit.response(),
+    };
     
     match NewsService::new().await {
         Ok(news_service) => {
-            match news_service.get_news(Some("event"), lang).await {
+            match news_service.get_news(Some("event"), lang, query.status).await {
                 Ok(news) => {
                     debug!("Returning {} event news items", news.len());
-                    HttpResponse::Ok().json(news)
+                    conditional.json(&req, &news)
                 },
                 Err(e) => {
                     error!("Failed to fetch event news: {}", e);
@@ -36,16 +49,20 @@ pub async fn get_news_events(query: web::Query<NewsQueryParams>) -> impl Respond
     }
 }
 
-pub async fn get_news_notices(query: web::Query<NewsQueryParams>) -> impl Responder {
+pub async fn get_news_notices(req: HttpRequest, query: web::Query<NewsQueryParams>) -> impl Responder {
     debug!("Handling request to get news notices");
-    let lang = query.lang.as_deref();
+    let lang = query.lang.as_deref().map_or("en-us", parse_language_code);
+    let conditional = match Conditional::check(&req, Dataset::News, format!("news:notice:{}", lang)) {
+        Ok(conditional) => conditional,
+        Err(hit) => return hit.response(),
+    };
     
     match NewsService::new().await {
         Ok(news_service) => {
-            match news_service.get_news(Some("notice"), lang).await {
+            match news_service.get_news(Some("notice"), lang, None).await {
                 Ok(news) => {
                     debug!("Returning {} notice news items", news.len());
-                    HttpResponse::Ok().json(news)
+                    conditional.json(&req, &news)
                 },
                 Err(e) => {
                     error!("Failed to fetch notice news: {}", e);
@@ -64,16 +81,20 @@ pub async fn get_news_notices(query: web::Query<NewsQueryParams>) -> impl Respon
     }
 }
 
-pub async fn get_news_info(query: web::Query<NewsQueryParams>) -> impl Responder {
+pub async fn get_news_info(req: HttpRequest, query: web::Query<NewsQueryParams>) -> impl Responder {
     debug!("Handling request to get news info");
-    let lang = query.lang.as_deref();
+    let lang = query.lang.as_deref().map_or("en-us", parse_language_code);
+    let conditional = match Conditional::check(&req, Dataset::News, format!("news:info:{}", lang)) {
+        Ok(conditional) => conditional,
+        Err(hit) => return hit.response(),
+    };
     
     match NewsService::new().await {
         Ok(news_service) => {
-            match news_service.get_news(Some("info"), lang).await {
+            match news_service.get_news(Some("info"), lang, None).await {
                 Ok(news) => {
                     debug!("Returning {} info news items", news.len());
-                    HttpResponse::Ok().json(news)
+                    conditional.json(&req, &news)
                 },
                 Err(e) => {
                     error!("Failed to fetch info news: {}", e);
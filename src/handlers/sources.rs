@@ -0,0 +1,21 @@
+use actix_web::{HttpResponse, Responder};
+use serde::Serialize;
+use log::debug;
+use crate::services::source_tracker::{self, SourceStatus};
+
+#[derive(Serialize)]
+pub struct SourceResponse {
+    #[serde(flatten)]
+    pub status: SourceStatus,
+    pub average_yield: Option<f64>,
+}
+
+pub async fn get_sources() -> impl Responder {
+    debug!("Handling request to get source status");
+    let sources: Vec<SourceResponse> = source_tracker::instance().snapshot()
+        .into_iter()
+        .map(|status| SourceResponse { average_yield: status.average_yield(), status })
+        .collect();
+
+    HttpResponse::Ok().json(sources)
+}
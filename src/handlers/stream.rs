@@ -0,0 +1,143 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web::http::header;
+use actix_web::web::Bytes;
+use futures::stream::{self, StreamExt};
+use log::{debug, warn};
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use crate::services::event_bus::{self, StreamEvent};
+
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+/// Reconnect delay suggested to EventSource clients, in milliseconds.
+const RETRY_MS: u64 = 5000;
+
+#[derive(Deserialize)]
+pub struct StreamQueryParams {
+    #[serde(rename = "lastEventId")]
+    pub last_event_id: Option<u64>,
+}
+
+/// The `Last-Event-ID` header set by reconnecting EventSource clients, or the
+/// `lastEventId` query parameter for clients that cannot set headers.
+fn last_event_id(req: &HttpRequest, query: &StreamQueryParams) -> Option<u64> {
+    req.headers()
+        .get("Last-Event-ID")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .or(query.last_event_id)
+}
+
+fn sse_frame(event: &StreamEvent) -> Bytes {
+    Bytes::from(format!("id: {}\nevent: {}\ndata: {}\n\n", event.id, event.event, event.data))
+}
+
+async fn next_frame(mut receiver: broadcast::Receiver<StreamEvent>) -> Option<(Result<Bytes, actix_web::Error>, broadcast::Receiver<StreamEvent>)> {
+    tokio::select! {
+        received = receiver.recv() => match received {
+            Ok(event) => Some((Ok(sse_frame(&event)), receiver)),
+            // Ending the stream makes the client reconnect with its Last-Event-ID
+            // and pick up the missed events from the replay buffer
+            Err(RecvError::Lagged(skipped)) => {
+                warn!("SSE client fell behind by {} events, closing stream", skipped);
+                None
+            },
+            Err(RecvError::Closed) => None,
+        },
+        _ = tokio::time::sleep(KEEP_ALIVE_INTERVAL) => {
+            Some((Ok(Bytes::from_static(b": keep-alive\n\n")), receiver))
+        },
+    }
+}
+
+pub async fn get_stream(req: HttpRequest, query: web::Query<StreamQueryParams>) -> HttpResponse {
+    let last_event_id = last_event_id(&req, &query);
+    debug!("Opening event stream (Last-Event-ID: {:?})", last_event_id);
+
+    let (backlog, receiver) = event_bus::instance().subscribe(last_event_id);
+    let retry = Bytes::from(format!("retry: {}\n\n", RETRY_MS));
+    let body = stream::once(async move { Ok(retry) })
+        .chain(stream::iter(backlog.iter().map(sse_frame).map(Ok).collect::<Vec<_>>()))
+        .chain(stream::unfold(receiver, next_frame));
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        // Keeps the Compress middleware from buffering the stream
+        .insert_header((header::CONTENT_ENCODING, "identity"))
+        .streaming(body)
+}
+
+pub async fn get_stream_ws(req: HttpRequest, body: web::Payload, query: web::Query<StreamQueryParams>) -> actix_web::Result<HttpResponse> {
+    let last_event_id = last_event_id(&req, &query);
+    let (response, mut session, mut messages) = actix_ws::handle(&req, body)?;
+    debug!("Opening WebSocket event stream (last event id: {:?})", last_event_id);
+
+    let (backlog, mut receiver) = event_bus::instance().subscribe(last_event_id);
+
+    actix_web::rt::spawn(async move {
+        for event in backlog {
+            if session.text(serde_json::to_string(&event).unwrap_or_default()).await.is_err() {
+                return;
+            }
+        }
+
+        loop {
+            tokio::select! {
+                received = receiver.recv() => match received {
+                    Ok(event) => {
+                        if session.text(serde_json::to_string(&event).unwrap_or_default()).await.is_err() {
+                            return;
+                        }
+                    },
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("WebSocket client fell behind by {} events, closing stream", skipped);
+                        break;
+                    },
+                    Err(RecvError::Closed) => break,
+                },
+                message = messages.next() => match message {
+                    Some(Ok(actix_ws::Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            return;
+                        }
+                    },
+                    Some(Ok(actix_ws::Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {},
+                },
+            }
+        }
+
+        let _ = session.close(None).await;
+    });
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn test_sse_frame() {
+        let event = StreamEvent {
+            id: 42,
+            event: "code.added".to_string(),
+            data: serde_json::json!({ "code": "STARRAILGIFT" }),
+        };
+        assert_eq!(sse_frame(&event), "id: 42\nevent: code.added\ndata: {\"code\":\"STARRAILGIFT\"}\n\n");
+    }
+
+    #[test]
+    fn test_last_event_id_prefers_header() {
+        let req = TestRequest::default()
+            .insert_header(("Last-Event-ID", "7"))
+            .to_http_request();
+        assert_eq!(last_event_id(&req, &StreamQueryParams { last_event_id: Some(3) }), Some(7));
+
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(last_event_id(&req, &StreamQueryParams { last_event_id: Some(3) }), Some(3));
+        assert_eq!(last_event_id(&req, &StreamQueryParams { last_event_id: None }), None);
+    }
+}
@@ -1,2 +1,3 @@
 pub mod resolvers;
+pub mod rewards;
 pub mod utils;
\ No newline at end of file
@@ -35,22 +35,22 @@ pub fn init_logger() {
                 .map(|s| s.to_owned())
                 .unwrap_or_else(|| format!("{:?}", std::thread::current().id()));
 
+            let colored_level = format!(
+                "{color}{: <5}{reset}",
+                record.level().to_string(),
+                color = level_color,
+                reset = reset_color,
+            );
+            let target = format!(
+                "\x1B[33m{module}{line}\x1B[0m",
+                module = record.module_path().unwrap_or("unknown"),
+                line = record.line().map_or(String::new(), |l| format!(":{}", l))
+            );
+
             writeln!(
                 buf,
-                "{timestamp} {colored_level} {thread} {target} → {message}",
+                "{timestamp} {colored_level} \x1B[35m[{thread_name}]\x1B[0m {target} → {message}",
                 timestamp = Local::now().format("\x1B[34m%Y-%m-%d %H:%M:%S%.3f\x1B[0m"),
-                colored_level = format!(
-                    "{color}{: <5}{reset}",
-                    record.level().to_string(),
-                    color = level_color,
-                    reset = reset_color,
-                ),
-                thread = format!("\x1B[35m[{thread_name}]\x1B[0m"),
-                target = format!(
-                    "\x1B[33m{module}{line}\x1B[0m",
-                    module = record.module_path().unwrap_or("unknown"),
-                    line = record.line().map_or(String::new(), |l| format!(":{}", l))
-                ),
                 message = record.args(),
             )
         })
@@ -1,12 +1,15 @@
 mod config;
 mod logger;
 mod resolvers;
+mod rewards;
 mod services;
 mod scheduler;
 mod utils;
 mod handlers;
 
 use actix_web::{web, App, HttpServer, middleware::Logger, middleware::Compress};
+use actix_web::body::MessageBody;
+use actix_web::middleware::{from_fn, Next};
 use log::{info, error};
 use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
 use actix_web::Error;
@@ -14,13 +17,20 @@ use actix_web::error::ErrorTooManyRequests;
 use futures_util::future::LocalBoxFuture;
 use std::future::{ready, Ready};
 use std::task::{Context, Poll};
+use std::time::Instant;
+use crate::services::metrics;
 use crate::services::rate_limiter::RateLimiter;
 use std::sync::Arc;
 use crate::config::Settings;
 use crate::handlers::{
     endpoints::get_api_endpoints,
-    codes::get_codes,
+    codes::{get_codes, get_code_detail, get_code_validations},
     news::{get_news_events, get_news_notices, get_news_info},
+    feeds::{get_code_rss, get_code_atom, get_news_rss, get_news_atom},
+    stream::{get_stream, get_stream_ws},
+    metrics::get_metrics,
+    health::{get_health, get_ready},
+    sources::get_sources,
 };
 
 pub struct RateLimiterMiddleware {
@@ -87,6 +97,7 @@ where
 
         Box::pin(async move {
             if !rate_limiter.check_rate_limit(&ip).await {
+                metrics::instance().rate_limited.inc();
                 return Err(ErrorTooManyRequests("Rate limit exceeded"));
             }
 
@@ -95,6 +106,18 @@ where
     }
 }
 
+/// Records request counts and latencies by matched route pattern, so `/starrail/code/{code}`
+/// is one series rather than one per code.
+async fn record_metrics(req: ServiceRequest, next: Next<impl MessageBody>) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let started = Instant::now();
+    let method = req.method().to_string();
+    let response = next.call(req).await?;
+
+    let route = response.request().match_pattern().unwrap_or_else(|| "unmatched".to_string());
+    metrics::instance().observe_request(&route, &method, response.status().as_u16(), started.elapsed());
+    Ok(response)
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     logger::init_logger();
@@ -114,12 +137,26 @@ async fn main() -> std::io::Result<()> {
         App::new()
             .wrap(Compress::default())
             .wrap(Logger::new("%a \"%r\" %s %b \"%{Referer}i\" \"%{User-Agent}i\" %T"))
+            .wrap(from_fn(record_metrics))
             .wrap(RateLimiterMiddleware::new(60, 60))
+            .route("/metrics", web::get().to(get_metrics))
+            .route("/health", web::get().to(get_health))
+            .route("/ready", web::get().to(get_ready))
             .route("/starrail", web::get().to(get_api_endpoints))
             .route("/starrail/code", web::get().to(get_codes))
+            // Feeds must be registered before /starrail/code/{code} would capture them
+            .route("/starrail/code/feed.xml", web::get().to(get_code_rss))
+            .route("/starrail/code/atom.xml", web::get().to(get_code_atom))
+            .route("/starrail/code/{code}", web::get().to(get_code_detail))
+            .route("/starrail/code/{code}/validations", web::get().to(get_code_validations))
             .route("/starrail/news/events", web::get().to(get_news_events))
             .route("/starrail/news/notices", web::get().to(get_news_notices))
             .route("/starrail/news/info", web::get().to(get_news_info))
+            .route("/starrail/news/{type}/feed.xml", web::get().to(get_news_rss))
+            .route("/starrail/news/{type}/atom.xml", web::get().to(get_news_atom))
+            .route("/starrail/sources", web::get().to(get_sources))
+            .route("/starrail/stream", web::get().to(get_stream))
+            .route("/starrail/stream/ws", web::get().to(get_stream_ws))
     })
     .bind(format!("{}:{}", config.server.host, config.server.port))
     .map_err(|e| {
@@ -1,19 +1,31 @@
 use async_trait::async_trait;
 use log::{debug, error};
-use reqwest::Client;
 use scraper::{Html, Selector};
 use mongodb::bson::DateTime;
 
-use super::{CodeResolver, RedemptionCode};
+use super::{CodeResolver, RedemptionCode, Server};
+use crate::utils::http::HttpClient;
+
+pub const DEFAULT_BASE_URL: &str = "https://www.eurogamer.net/honkai-star-rail-codes-livestream-active-working-how-to-redeem-9321";
 
 pub struct EurogamerResolver {
-    client: Client,
+    client: HttpClient,
+    base_url: String,
 }
 
 impl EurogamerResolver {
     pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self::with_client(base_url, HttpClient::default())
+    }
+
+    pub fn with_client(base_url: impl Into<String>, client: HttpClient) -> Self {
         Self {
-            client: Client::new(),
+            client,
+            base_url: base_url.into(),
         }
     }
 
@@ -43,9 +55,17 @@ impl EurogamerResolver {
                         id: None,
                         code,
                         rewards,
+                        items: Vec::new(),
                         source: "Eurogamer".to_string(),
+                        sources: Vec::new(),
                         date: Some(DateTime::now()),
                         active: true,
+                        first_seen_at: None,
+                        last_seen_in_source_at: None,
+                        last_validated_at: None,
+                        deactivated_at: None,
+                        server: Server::Global,
+                        regions: Vec::new(),
                     });
                 }
             }
@@ -56,12 +76,12 @@ impl EurogamerResolver {
         if let Some(table) = document.select(&table_selector).next() {
             let mut current_code = String::new();
             let mut current_rewards = Vec::new();
-            let mut count = 0;
 
-            for cell in table.text().collect::<String>().split('\n')
+            for (count, cell) in table.text().collect::<String>().split('\n')
                 .map(|s| s.trim())
                 .filter(|s| !s.is_empty())
                 .skip(3) // Skip header rows
+                .enumerate()
             {
                 match count % 3 {
                     0 => current_code = cell.to_string(),
@@ -82,15 +102,22 @@ impl EurogamerResolver {
                                 id: None,
                                 code: current_code.clone(),
                                 rewards: current_rewards.clone(),
+                                items: Vec::new(),
                                 source: "Eurogamer".to_string(),
+                                sources: Vec::new(),
                                 date: Some(DateTime::now()),
                                 active: true,
+                                first_seen_at: None,
+                                last_seen_in_source_at: None,
+                                last_validated_at: None,
+                                deactivated_at: None,
+                                server: Server::Global,
+                                regions: Vec::new(),
                             });
                         }
                     },
                     _ => unreachable!(),
                 }
-                count += 1;
             }
         }
 
@@ -99,6 +126,12 @@ impl EurogamerResolver {
     }
 }
 
+impl Default for EurogamerResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl CodeResolver for EurogamerResolver {
     fn name(&self) -> String {
@@ -106,17 +139,13 @@ impl CodeResolver for EurogamerResolver {
     }
 
     fn base_url(&self) -> String {
-        "https://www.eurogamer.net/honkai-star-rail-codes-livestream-active-working-how-to-redeem-9321".to_string()
+        self.base_url.clone()
     }
 
     async fn fetch_codes(&self) -> Result<Vec<RedemptionCode>, Box<dyn std::error::Error + Send + Sync>> {
         debug!("Fetching codes from Eurogamer");
         
-        let response = self.client
-            .get(self.base_url())
-            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36")
-            .send()
-            .await?;
+        let response = self.client.send(self.client.get(self.base_url())).await?;
 
         if !response.status().is_success() {
             error!("Failed to fetch Eurogamer page: {}", response.status());
@@ -129,4 +158,5 @@ impl CodeResolver for EurogamerResolver {
 }
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests; 
\ No newline at end of file
@@ -4,7 +4,10 @@ mod tests {
     use crate::resolvers::CodeResolver;
     use tokio;
 
+    const FIXTURE: &str = include_str!("../fixtures/eurogamer.html");
+
     #[tokio::test]
+    #[ignore = "hits the live site"]
     async fn test_fetch_eurogamer_codes() {
         let resolver = EurogamerResolver::new();
         let codes = resolver.fetch_codes().await.unwrap();
@@ -19,15 +22,11 @@ mod tests {
     }
 
     #[tokio::test]
+    #[ignore = "hits the live site"]
     async fn test_parse_html_from_live_site() {
         let resolver = EurogamerResolver::new();
         
-        let response = resolver.client
-            .get(resolver.base_url())
-            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36")
-            .send()
-            .await
-            .unwrap();
+        let response = resolver.client.send(resolver.client.get(resolver.base_url())).await.unwrap();
 
         assert!(response.status().is_success(), "Failed to fetch page");
         
@@ -50,4 +49,18 @@ mod tests {
             assert!(code.code.chars().all(|c| c.is_ascii_alphanumeric()), "Code should only contain alphanumeric characters");
         }
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_parse_html_fixture() {
+        let resolver = EurogamerResolver::new();
+        let codes = resolver.parse_html(FIXTURE);
+
+        // The code list comes first, followed by the livestream table
+        let found: Vec<&str> = codes.iter().map(|code| code.code.as_str()).collect();
+        assert_eq!(found, vec!["STARRAILGIFT", "VAJEGY4MNMDK", "HSR2024ABCDE", "LIVESTREAM3X9K", "LIVESTREAM7Q2M"]);
+
+        assert_eq!(codes[0].rewards, vec!["50 Stellar Jade", "10,000 Credits"]);
+        assert_eq!(codes[1].rewards, vec!["100 Stellar Jade", "3 Traveler's Guide", "4 Refined Aether"]);
+        assert_eq!(codes[3].rewards, vec!["100 Stellar Jade", "50,000 Credits"]);
+    }
+}
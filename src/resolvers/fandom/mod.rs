@@ -1,20 +1,32 @@
 use async_trait::async_trait;
 use log::{debug, error};
-use reqwest::Client;
 use scraper::{Html, Selector};
 use mongodb::bson::DateTime;
 use regex::Regex;
 
-use super::{CodeResolver, RedemptionCode};
+use super::{CodeResolver, RedemptionCode, Server};
+use crate::utils::http::HttpClient;
+
+pub const DEFAULT_BASE_URL: &str = "https://honkai-star-rail.fandom.com/wiki/Redemption_Code";
 
 pub struct FandomResolver {
-    client: Client,
+    client: HttpClient,
+    base_url: String,
 }
 
 impl FandomResolver {
     pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self::with_client(base_url, HttpClient::default())
+    }
+
+    pub fn with_client(base_url: impl Into<String>, client: HttpClient) -> Self {
         Self {
-            client: Client::new(),
+            client,
+            base_url: base_url.into(),
         }
     }
 
@@ -33,15 +45,14 @@ impl FandomResolver {
                 .collect();
 
             let code_regex = Regex::new(r"HSRGRANDOPEN[0-9]|[A-Z0-9]{11,15}").unwrap();
-            let cleanup_regex = Regex::new(r"All|\[\d+\]|Quick Redeem|CodeServerRewardsDuration").unwrap();
+            let cleanup_regex = Regex::new(r"All|China|\[\d+\]|Quick Redeem|CodeServerRewardsDuration").unwrap();
             let amount_regex = Regex::new(r"×\d+").unwrap();
             
             for row in table_list {
-                if row.contains("China") {
-                    continue;
-                }
+                // The server column reads "China" for codes that only work there
+                let server = if row.contains("China") { Server::Cn } else { Server::Global };
 
-                let clean_text = cleanup_regex.replace_all(row, "").trim().to_string();
+                let clean_text = cleanup_regex.replace_all(row, " ").trim().to_string();
                 if let Some(code_match) = code_regex.find(&clean_text) {
                     let code = code_match.as_str().to_string();
                     
@@ -73,9 +84,17 @@ impl FandomResolver {
                             id: None,
                             code,
                             rewards,
+                            items: Vec::new(),
                             source: "star-rail-fandom".to_string(),
+                            sources: Vec::new(),
                             date: Some(DateTime::now()),
                             active: true,
+                            first_seen_at: None,
+                            last_seen_in_source_at: None,
+                            last_validated_at: None,
+                            deactivated_at: None,
+                            server,
+                            regions: Vec::new(),
                         });
                     }
                 }
@@ -87,6 +106,12 @@ impl FandomResolver {
     }
 }
 
+impl Default for FandomResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl CodeResolver for FandomResolver {
     fn name(&self) -> String {
@@ -94,17 +119,13 @@ impl CodeResolver for FandomResolver {
     }
 
     fn base_url(&self) -> String {
-        "https://honkai-star-rail.fandom.com/wiki/Redemption_Code".to_string()
+        self.base_url.clone()
     }
 
     async fn fetch_codes(&self) -> Result<Vec<RedemptionCode>, Box<dyn std::error::Error + Send + Sync>> {
         debug!("Fetching codes from Fandom");
         
-        let response = self.client
-            .get(self.base_url())
-            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36")
-            .send()
-            .await?;
+        let response = self.client.send(self.client.get(self.base_url())).await?;
 
         if !response.status().is_success() {
             error!("Failed to fetch Fandom page: {}", response.status());
@@ -117,4 +138,5 @@ impl CodeResolver for FandomResolver {
 }
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests;
\ No newline at end of file
@@ -1,19 +1,31 @@
 use async_trait::async_trait;
 use log::{debug, error};
-use reqwest::Client;
 use scraper::{Html, Selector};
 use mongodb::bson::DateTime;
 
-use super::{CodeResolver, RedemptionCode};
+use super::{CodeResolver, RedemptionCode, Server};
+use crate::utils::http::HttpClient;
+
+pub const DEFAULT_BASE_URL: &str = "https://game8.co/games/Honkai-Star-Rail/archives/410296";
 
 pub struct Game8Resolver {
-    client: Client,
+    client: HttpClient,
+    base_url: String,
 }
 
 impl Game8Resolver {
     pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self::with_client(base_url, HttpClient::default())
+    }
+
+    pub fn with_client(base_url: impl Into<String>, client: HttpClient) -> Self {
         Self {
-            client: Client::new(),
+            client,
+            base_url: base_url.into(),
         }
     }
 
@@ -57,8 +69,8 @@ impl Game8Resolver {
                             
                             for part in rewards_text.split(',') {
                                 let part = part.trim();
-                                if current_reward.chars().last().map_or(false, |c| c.is_ascii_digit()) 
-                                   && part.chars().next().map_or(false, |c| c.is_ascii_digit()) {
+                                if current_reward.chars().last().is_some_and(|c| c.is_ascii_digit()) 
+                                   && part.chars().next().is_some_and(|c| c.is_ascii_digit()) {
                                     // If current reward ends with number and next part starts with number,
                                     // treat it as a thousands separator
                                     current_reward.push(',');
@@ -81,9 +93,17 @@ impl Game8Resolver {
                                     id: None,
                                     code,
                                     rewards,
+                                    items: Vec::new(),
                                     source: "Game8".to_string(),
+                                    sources: Vec::new(),
                                     date: Some(DateTime::now()),
                                     active: true,
+                                    first_seen_at: None,
+                                    last_seen_in_source_at: None,
+                                    last_validated_at: None,
+                                    deactivated_at: None,
+                                    server: Server::Global,
+                                    regions: Vec::new(),
                                 });
                             }
                         }
@@ -98,6 +118,12 @@ impl Game8Resolver {
     }
 }
 
+impl Default for Game8Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl CodeResolver for Game8Resolver {
     fn name(&self) -> String {
@@ -105,17 +131,13 @@ impl CodeResolver for Game8Resolver {
     }
 
     fn base_url(&self) -> String {
-        "https://game8.co/games/Honkai-Star-Rail/archives/410296".to_string()
+        self.base_url.clone()
     }
 
     async fn fetch_codes(&self) -> Result<Vec<RedemptionCode>, Box<dyn std::error::Error + Send + Sync>> {
         debug!("Fetching codes from Game8");
         
-        let response = self.client
-            .get(self.base_url())
-            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36")
-            .send()
-            .await?;
+        let response = self.client.send(self.client.get(self.base_url())).await?;
 
         if !response.status().is_success() {
             error!("Failed to fetch Game8 page: {}", response.status());
@@ -128,4 +150,5 @@ impl CodeResolver for Game8Resolver {
 }
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests; 
\ No newline at end of file
@@ -1,10 +1,13 @@
 use async_trait::async_trait;
 use log::{debug, error, info};
-use reqwest::Client;
+use reqwest::RequestBuilder;
 use serde::Deserialize;
 use mongodb::bson::DateTime;
 
-use super::{CodeResolver, RedemptionCode};
+use super::{CodeResolver, RedemptionCode, Server};
+use crate::utils::http::HttpClient;
+use crate::rewards::Reward;
+use crate::rewards::catalog::{self, CatalogItem};
 
 #[derive(Debug, Deserialize)]
 struct HoyolabResponse {
@@ -41,63 +44,55 @@ struct IconBonus {
     icon_url: String,
 }
 
+pub const DEFAULT_BASE_URL: &str = "https://bbs-api-os.hoyolab.com/community/painter/wapi/circle/channel/guide/material";
+pub const QUERY: &[(&str, &str)] = &[("game_id", "6")];
+
+/// Adds the headers the HoYoLAB web API expects from its own client.
+pub fn with_api_headers(request: RequestBuilder) -> RequestBuilder {
+    request
+        .header("x-rpc-app_version", "2.42.0")
+        .header("x-rpc-client_type", "4")
+}
+
 pub struct HoyolabResolver {
-    client: Client,
+    client: HttpClient,
+    base_url: String,
 }
 
 impl HoyolabResolver {
     pub fn new() -> Self {
-        Self {
-            client: Client::new(),
-        }
+        Self::with_base_url(DEFAULT_BASE_URL)
     }
 
-    fn map_reward_type(&self, icon_url: &str) -> &str {
-        match icon_url {
-            url if url.contains("77cb5426637574ba524ac458fa963da0_6409817950389238658") => "Stellar Jade",
-            url if url.contains("7cb0e487e051f177d3f41de8d4bbc521_2556290033227986328") => "Refined Aether",
-            url if url.contains("508229a94e4fa459651f64c1cd02687a_6307505132287490837") => "Traveler's Guide",
-            url if url.contains("0b12bdf76fa4abc6b4d1fdfc0fb4d6f5_4521150989210768295") => "Credit",
-            _ => "Unknown",
-        }
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self::with_client(base_url, HttpClient::default())
     }
-}
 
-#[async_trait]
-impl CodeResolver for HoyolabResolver {
-    fn name(&self) -> String {
-        "Hoyolab".to_string()
+    pub fn with_client(base_url: impl Into<String>, client: HttpClient) -> Self {
+        Self {
+            client,
+            base_url: base_url.into(),
+        }
     }
 
-    fn base_url(&self) -> String {
-        "https://bbs-api-os.hoyolab.com/community/painter/wapi/circle/channel/guide/material".to_string()
+    fn map_reward_item(&self, icon_url: &str) -> Option<&'static CatalogItem> {
+        let item_id = match icon_url {
+            url if url.contains("77cb5426637574ba524ac458fa963da0_6409817950389238658") => "stellar_jade",
+            url if url.contains("7cb0e487e051f177d3f41de8d4bbc521_2556290033227986328") => "refined_aether",
+            url if url.contains("508229a94e4fa459651f64c1cd02687a_6307505132287490837") => "travelers_guide",
+            url if url.contains("0b12bdf76fa4abc6b4d1fdfc0fb4d6f5_4521150989210768295") => "credit",
+            _ => return None,
+        };
+        catalog::find_by_id(item_id)
     }
 
-    async fn fetch_codes(&self) -> Result<Vec<RedemptionCode>, Box<dyn std::error::Error + Send + Sync>> {
-        debug!("Fetching codes from Hoyolab API");
-        
-        let response = self.client
-            .get(self.base_url())
-            .query(&[("game_id", "6")])
-            .header("x-rpc-app_version", "2.42.0")
-            .header("x-rpc-client_type", "4")
-            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36")
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            error!("Failed to fetch Hoyolab API: {}", response.status());
-            return Err("Failed to fetch Hoyolab API".into());
-        }
-
-        let hoyolab_response: HoyolabResponse = response.json().await?;
-        
+    fn parse_response(&self, hoyolab_response: HoyolabResponse) -> Vec<RedemptionCode> {
         if hoyolab_response.retcode != 0 {
             info!("Hoyolab API returned non-zero retcode: {} - {}", 
                 hoyolab_response.retcode, 
                 hoyolab_response.message
             );
-            return Ok(Vec::new());
+            return Vec::new();
         }
 
         let mut codes = Vec::new();
@@ -109,23 +104,38 @@ impl CodeResolver for HoyolabResolver {
             {
                 for bonus in &exchange_group.bonuses {
                     if bonus.code_status == "ON" {
-                        let rewards: Vec<String> = bonus.icon_bonuses.iter()
+                        let items: Vec<Reward> = bonus.icon_bonuses.iter()
                             .map(|icon_bonus| {
-                                format!("{} {}", 
-                                    icon_bonus.bonus_num,
-                                    self.map_reward_type(&icon_bonus.icon_url)
-                                )
+                                let item = self.map_reward_item(&icon_bonus.icon_url);
+                                Reward {
+                                    item_id: item.map(|item| item.id.to_string()),
+                                    item_name: item.map_or("Unknown", |item| item.name).to_string(),
+                                    amount: icon_bonus.bonus_num.parse().ok(),
+                                }
                             })
                             .collect();
 
+                        let rewards: Vec<String> = bonus.icon_bonuses.iter()
+                            .zip(&items)
+                            .map(|(icon_bonus, item)| format!("{} {}", icon_bonus.bonus_num, item.item_name))
+                            .collect();
+
                         if !rewards.is_empty() {
                             codes.push(RedemptionCode {
                                 id: None,
                                 code: bonus.exchange_code.clone(),
                                 rewards,
+                                items,
                                 source: "Hoyolab".to_string(),
+                                sources: Vec::new(),
                                 date: Some(DateTime::now()),
                                 active: true,
+                                first_seen_at: None,
+                                last_seen_in_source_at: None,
+                                last_validated_at: None,
+                                deactivated_at: None,
+                                server: Server::Global,
+                                regions: Vec::new(),
                             });
                         }
                     }
@@ -136,9 +146,42 @@ impl CodeResolver for HoyolabResolver {
         }
 
         debug!("Found {} codes from Hoyolab", codes.len());
-        Ok(codes)
+        codes
+    }
+}
+
+impl Default for HoyolabResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl CodeResolver for HoyolabResolver {
+    fn name(&self) -> String {
+        "Hoyolab".to_string()
+    }
+
+    fn base_url(&self) -> String {
+        self.base_url.clone()
+    }
+
+    async fn fetch_codes(&self) -> Result<Vec<RedemptionCode>, Box<dyn std::error::Error + Send + Sync>> {
+        debug!("Fetching codes from Hoyolab API");
+        
+        let request = with_api_headers(self.client.get(self.base_url()).query(QUERY));
+        let response = self.client.send(request).await?;
+
+        if !response.status().is_success() {
+            error!("Failed to fetch Hoyolab API: {}", response.status());
+            return Err("Failed to fetch Hoyolab API".into());
+        }
+
+        let hoyolab_response: HoyolabResponse = response.json().await?;
+        Ok(self.parse_response(hoyolab_response))
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests; 
\ No newline at end of file
@@ -0,0 +1,196 @@
+use async_trait::async_trait;
+use log::{debug, error};
+use scraper::{ElementRef, Html, Selector};
+use serde::Deserialize;
+use mongodb::bson::DateTime;
+use regex::Regex;
+
+use super::{CodeResolver, RedemptionCode, Server};
+use crate::utils::http::HttpClient;
+
+pub const DEFAULT_CODE_PATTERN: &str = r"\b[A-Z0-9]{6,20}\b";
+
+/// A code site described in config instead of code, scraped by [`HtmlResolver`].
+#[derive(Debug, Deserialize, Clone)]
+pub struct SiteDefinition {
+    pub name: String,
+    pub url: String,
+    /// CSS selector matching one element per code.
+    pub entry: String,
+    /// CSS selector for the code inside an entry. The whole entry when unset.
+    #[serde(default)]
+    pub code: Option<String>,
+    /// CSS selector for the rewards inside an entry. When unset, the entry's text with the
+    /// code removed.
+    #[serde(default)]
+    pub rewards: Option<String>,
+    /// Separators between rewards. A `,` between two digits is kept as a thousands separator.
+    #[serde(default = "default_separators")]
+    pub separators: Vec<String>,
+    /// Text removed from the rewards before splitting, e.g. "NEW" badges or brackets.
+    #[serde(default)]
+    pub strip: Vec<String>,
+    /// Regex finding the code in the code text. The first capture group is used if there is one.
+    #[serde(default = "default_code_pattern")]
+    pub code_pattern: String,
+    /// Server the site's codes are redeemed on.
+    #[serde(default)]
+    pub server: Server,
+}
+
+fn default_separators() -> Vec<String> {
+    vec![",".to_string()]
+}
+
+fn default_code_pattern() -> String {
+    DEFAULT_CODE_PATTERN.to_string()
+}
+
+/// Generic resolver for sites that list codes in plain HTML, driven by a [`SiteDefinition`].
+pub struct HtmlResolver {
+    client: HttpClient,
+    definition: SiteDefinition,
+    entry: Selector,
+    code: Option<Selector>,
+    rewards: Option<Selector>,
+    code_pattern: Regex,
+}
+
+impl HtmlResolver {
+    pub fn new(definition: SiteDefinition) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::with_client(definition, HttpClient::default())
+    }
+
+    /// Compiles the selectors and code pattern of `definition`, failing if any is invalid.
+    pub fn with_client(definition: SiteDefinition, client: HttpClient) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let parse = |selector: &str| {
+            Selector::parse(selector)
+                .map_err(|e| format!("{}: invalid selector {:?}: {}", definition.name, selector, e))
+        };
+
+        let entry = parse(&definition.entry)?;
+        let code = definition.code.as_deref().map(parse).transpose()?;
+        let rewards = definition.rewards.as_deref().map(parse).transpose()?;
+        let code_pattern = Regex::new(&definition.code_pattern)
+            .map_err(|e| format!("{}: invalid code pattern: {}", definition.name, e))?;
+
+        Ok(Self {
+            client,
+            entry,
+            code,
+            rewards,
+            code_pattern,
+            definition,
+        })
+    }
+
+    fn parse_html(&self, html: &str) -> Vec<RedemptionCode> {
+        debug!("Parsing {} HTML content", self.definition.name);
+        let document = Html::parse_document(html);
+        let mut codes = Vec::new();
+
+        for entry in document.select(&self.entry) {
+            let code_text = match &self.code {
+                Some(selector) => entry.select(selector).next().map(text),
+                None => Some(text(entry)),
+            };
+            let Some(code_text) = code_text else { continue };
+
+            let Some(found) = self.code_pattern.captures(&code_text) else { continue };
+            let code = found.get(1).or_else(|| found.get(0)).map(|m| m.as_str().trim().to_string()).unwrap_or_default();
+            if code.is_empty() {
+                continue;
+            }
+
+            let mut rewards_text = match &self.rewards {
+                Some(selector) => entry.select(selector).next().map(text).unwrap_or_default(),
+                None => text(entry).replace(&code, ""),
+            };
+            for strip in &self.definition.strip {
+                rewards_text = rewards_text.replace(strip.as_str(), "");
+            }
+
+            codes.push(RedemptionCode {
+                id: None,
+                code,
+                rewards: split_rewards(&rewards_text, &self.definition.separators),
+                items: Vec::new(),
+                source: self.definition.name.clone(),
+                sources: Vec::new(),
+                date: Some(DateTime::now()),
+                active: true,
+                first_seen_at: None,
+                last_seen_in_source_at: None,
+                last_validated_at: None,
+                deactivated_at: None,
+                server: self.definition.server,
+                regions: Vec::new(),
+            });
+        }
+
+        debug!("Found {} codes from {}", codes.len(), self.definition.name);
+        codes
+    }
+}
+
+fn text(element: ElementRef) -> String {
+    element.text().collect::<String>().trim().to_string()
+}
+
+fn split_rewards(text: &str, separators: &[String]) -> Vec<String> {
+    let mut rewards = vec![text.to_string()];
+    for separator in separators.iter().filter(|separator| !separator.is_empty()) {
+        rewards = rewards.iter()
+            .flat_map(|reward| {
+                let mut parts: Vec<String> = Vec::new();
+                for part in reward.split(separator.as_str()) {
+                    match parts.last_mut() {
+                        // "Credit x10,000" is one reward
+                        Some(previous) if separator == ","
+                            && previous.ends_with(|c: char| c.is_ascii_digit())
+                            && part.starts_with(|c: char| c.is_ascii_digit()) => {
+                            previous.push(',');
+                            previous.push_str(part);
+                        },
+                        _ => parts.push(part.to_string()),
+                    }
+                }
+                parts
+            })
+            .collect();
+    }
+
+    rewards.into_iter()
+        .map(|reward| reward.trim().to_string())
+        .filter(|reward| !reward.is_empty())
+        .collect()
+}
+
+#[async_trait]
+impl CodeResolver for HtmlResolver {
+    fn name(&self) -> String {
+        self.definition.name.clone()
+    }
+
+    fn base_url(&self) -> String {
+        self.definition.url.clone()
+    }
+
+    async fn fetch_codes(&self) -> Result<Vec<RedemptionCode>, Box<dyn std::error::Error + Send + Sync>> {
+        debug!("Fetching codes from {}", self.definition.name);
+
+        let response = self.client.send(self.client.get(self.base_url())).await?;
+
+        if !response.status().is_success() {
+            error!("Failed to fetch {} page: {}", self.definition.name, response.status());
+            return Err(format!("Failed to fetch {} page", self.definition.name).into());
+        }
+
+        let html = response.text().await?;
+        Ok(self.parse_html(&html))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests;
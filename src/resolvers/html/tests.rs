@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use crate::resolvers::html::{split_rewards, HtmlResolver, SiteDefinition};
+    use crate::resolvers::{CodeResolver, Server};
+    use serde_json::json;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use wiremock::matchers::{method, path};
+
+    const GAME8_FIXTURE: &str = include_str!("../fixtures/game8.html");
+    const PRYDWEN_FIXTURE: &str = include_str!("../fixtures/prydwen.html");
+
+    fn definition(value: serde_json::Value) -> SiteDefinition {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn game8() -> SiteDefinition {
+        definition(json!({
+            "name": "Game8 (config)",
+            "url": "https://game8.co/games/Honkai-Star-Rail/archives/410296",
+            "entry": "h2#hl_1 + ul.a-list > li.a-listItem",
+            "code": "a.a-link",
+            "strip": ["NEW", "(", ")"],
+            "code_pattern": "^([A-Z0-9]{8,})$",
+        }))
+    }
+
+    #[test]
+    fn test_parse_game8_fixture() {
+        let resolver = HtmlResolver::new(game8()).unwrap();
+        let codes = resolver.parse_html(GAME8_FIXTURE);
+
+        let found: Vec<&str> = codes.iter().map(|code| code.code.as_str()).collect();
+        assert_eq!(found, vec!["STARRAILGIFT", "VAJEGY4MNMDK", "HSR2024ABCDE"]);
+        assert_eq!(codes[0].rewards, vec!["Stellar Jade x50", "Credit x10,000"]);
+        assert_eq!(codes[1].rewards, vec!["Stellar Jade x100", "Traveler's Guide x3", "Refined Aether x4"]);
+        assert!(codes.iter().all(|code| code.source == "Game8 (config)"));
+    }
+
+    #[test]
+    fn test_parse_prydwen_fixture() {
+        let resolver = HtmlResolver::new(definition(json!({
+            "name": "Prydwen (config)",
+            "url": "https://www.prydwen.gg/star-rail/",
+            "entry": ".codes .box",
+            "code": ".code",
+            "rewards": ".rewards",
+            "separators": [" + "],
+            "server": "cn",
+        }))).unwrap();
+        let codes = resolver.parse_html(PRYDWEN_FIXTURE);
+        assert!(codes.iter().all(|code| code.server == Server::Cn));
+
+        let found: Vec<&str> = codes.iter().map(|code| code.code.as_str()).collect();
+        assert_eq!(found, vec!["STARRAILGIFT", "VAJEGY4MNMDK", "HSR2024ABCDE"]);
+        assert_eq!(codes[0].rewards, vec!["50 Stellar Jade", "10000 Credits"]);
+    }
+
+    #[test]
+    fn test_split_rewards() {
+        let separators = vec![" and ".to_string(), ",".to_string()];
+        assert_eq!(
+            split_rewards("100 Stellar Jade, 2 Traveler's Guide and 50,000 Credits", &separators),
+            vec!["100 Stellar Jade", "2 Traveler's Guide", "50,000 Credits"],
+        );
+    }
+
+    #[test]
+    fn test_invalid_definition() {
+        let mut invalid = game8();
+        invalid.entry = "li[".to_string();
+        assert!(HtmlResolver::new(invalid).is_err());
+
+        let mut invalid = game8();
+        invalid.code_pattern = "([A-Z".to_string();
+        assert!(HtmlResolver::new(invalid).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_codes() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/codes"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(GAME8_FIXTURE))
+            .mount(&server)
+            .await;
+
+        let mut definition = game8();
+        definition.url = format!("{}/codes", server.uri());
+        let codes = HtmlResolver::new(definition).unwrap().fetch_codes().await.unwrap();
+        assert_eq!(codes.len(), 3);
+    }
+}
@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use mongodb::bson::{DateTime, oid::ObjectId};
+use crate::rewards::Reward;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RedemptionCode {
@@ -10,11 +11,109 @@ pub struct RedemptionCode {
     #[serde(default)]
     pub rewards: Vec<String>,
     #[serde(default)]
+    pub items: Vec<Reward>,
+    #[serde(default)]
     pub source: String,
+    #[serde(default)]
+    pub sources: Vec<CodeSource>,
     #[serde(default = "default_as_true")]
     pub active: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub date: Option<DateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_seen_at: Option<DateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seen_in_source_at: Option<DateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_validated_at: Option<DateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deactivated_at: Option<DateTime>,
+    /// Codes saved before China-server support are all global.
+    #[serde(default)]
+    pub server: Server,
+    /// Latest conclusive validation in each region of `server` that has a validator account.
+    #[serde(default)]
+    pub regions: Vec<RegionStatus>,
+}
+
+/// Game server a code can be redeemed on. The China server has its own accounts and
+/// redeem endpoint.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Server {
+    #[default]
+    Global,
+    Cn,
+}
+
+impl Server {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Server::Global => "global",
+            Server::Cn => "cn",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "global" => Some(Server::Global),
+            "cn" => Some(Server::Cn),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CodeSource {
+    pub name: String,
+    pub first_seen_at: DateTime,
+    pub last_seen_at: DateTime,
+    #[serde(default)]
+    pub rewards: Vec<String>,
+}
+
+/// Whether a code worked the last time it was redeemed in a game region, such as
+/// `prod_official_usa` or `prod_official_asia`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RegionStatus {
+    pub region: String,
+    pub valid: bool,
+    pub checked_at: DateTime,
+}
+
+impl CodeSource {
+    pub fn new(name: &str, rewards: &[String], seen_at: DateTime) -> Self {
+        Self {
+            name: name.to_string(),
+            first_seen_at: seen_at,
+            last_seen_at: seen_at,
+            rewards: rewards.to_vec(),
+        }
+    }
+}
+
+impl RedemptionCode {
+    /// Merges a sighting into `sources`, keeping the earliest first-seen time per source.
+    pub fn record_source(&mut self, sighting: CodeSource) {
+        match self.sources.iter_mut().find(|source| source.name == sighting.name) {
+            Some(existing) => {
+                existing.first_seen_at = existing.first_seen_at.min(sighting.first_seen_at);
+                if sighting.last_seen_at >= existing.last_seen_at {
+                    existing.last_seen_at = sighting.last_seen_at;
+                    existing.rewards = sighting.rewards;
+                }
+            },
+            None => self.sources.push(sighting),
+        }
+    }
+
+    /// Replaces the status of `status.region`, keeping regions in the order they were first checked.
+    pub fn record_region(&mut self, status: RegionStatus) {
+        match self.regions.iter_mut().find(|existing| existing.region == status.region) {
+            Some(existing) => *existing = status,
+            None => self.regions.push(status),
+        }
+    }
 }
 
 fn default_as_true() -> bool {
@@ -34,4 +133,58 @@ pub mod fandom;
 pub mod polygon;
 pub mod prydwen;
 pub mod hoyolab;
-pub mod news;
\ No newline at end of file
+pub mod html;
+pub mod news;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code_with_sources(sources: Vec<CodeSource>) -> RedemptionCode {
+        RedemptionCode {
+            id: None,
+            code: "STARRAILGIFT".to_string(),
+            rewards: Vec::new(),
+            items: Vec::new(),
+            source: String::new(),
+            sources,
+            active: true,
+            date: None,
+            first_seen_at: None,
+            last_seen_in_source_at: None,
+            last_validated_at: None,
+            deactivated_at: None,
+            server: Server::Global,
+            regions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_record_source_merges_by_name() {
+        let earlier = DateTime::from_millis(1_000);
+        let later = DateTime::from_millis(2_000);
+
+        let mut code = code_with_sources(vec![CodeSource::new("Game8", &["50 Stellar Jade".to_string()], earlier)]);
+        code.record_source(CodeSource::new("Game8", &["Stellar Jade x50".to_string()], later));
+        code.record_source(CodeSource::new("Prydwen", &[], later));
+
+        assert_eq!(code.sources.len(), 2);
+        assert_eq!(code.sources[0].first_seen_at, earlier);
+        assert_eq!(code.sources[0].last_seen_at, later);
+        assert_eq!(code.sources[0].rewards, vec!["Stellar Jade x50".to_string()]);
+        assert_eq!(code.sources[1].name, "Prydwen");
+    }
+
+    #[test]
+    fn test_record_source_ignores_stale_rewards() {
+        let earlier = DateTime::from_millis(1_000);
+        let later = DateTime::from_millis(2_000);
+
+        let mut code = code_with_sources(vec![CodeSource::new("Fandom", &["new".to_string()], later)]);
+        code.record_source(CodeSource::new("Fandom", &["old".to_string()], earlier));
+
+        assert_eq!(code.sources[0].first_seen_at, earlier);
+        assert_eq!(code.sources[0].last_seen_at, later);
+        assert_eq!(code.sources[0].rewards, vec!["new".to_string()]);
+    }
+}
@@ -1,8 +1,9 @@
 use serde::{Deserialize, Serialize};
-use reqwest::Client;
+use crate::utils::http::HttpClient;
+use super::hoyolab::with_api_headers;
 use mongodb::bson::oid::ObjectId;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct NewsItem {
     #[allow(dead_code)]
     #[serde(skip_serializing)]
@@ -21,6 +22,10 @@ pub struct NewsItem {
     #[serde(rename = "type")]
     pub news_type: String,
     pub lang: String,
+    #[serde(rename = "startAt", alias = "start_at", default, skip_serializing_if = "Option::is_none")]
+    pub start_at: Option<i64>,
+    #[serde(rename = "endAt", alias = "end_at", default, skip_serializing_if = "Option::is_none")]
+    pub end_at: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -40,6 +45,10 @@ struct EventItem {
     desc: String,
     #[serde(deserialize_with = "deserialize_timestamp")]
     create_at: i64,
+    #[serde(default, deserialize_with = "deserialize_optional_timestamp")]
+    start: Option<i64>,
+    #[serde(default, deserialize_with = "deserialize_optional_timestamp")]
+    end: Option<i64>,
     banner_url: String,
 }
 
@@ -68,8 +77,18 @@ struct NewsList {
     list: Vec<NewsPost>,
 }
 
+pub const DEFAULT_HOST: &str = "https://bbs-api-os.hoyolab.com";
+pub const EVENT_LIST_PATH: &str = "/community/community_contribution/wapi/event/list";
+pub const NEWS_LIST_PATH: &str = "/community/post/wapi/getNewsList";
+pub const EVENT_LIST_QUERY: &[(&str, &str)] = &[("page_size", "15"), ("size", "15"), ("gids", "6")];
+/// Sent with a `type` of `NOTICE_TYPE` or `INFO_TYPE`.
+pub const NEWS_LIST_QUERY: &[(&str, &str)] = &[("gids", "6"), ("page_size", "15")];
+pub const NOTICE_TYPE: i32 = 1;
+pub const INFO_TYPE: i32 = 3;
+
 pub struct NewsResolver {
-    client: Client,
+    client: HttpClient,
+    host: String,
 }
 
 fn deserialize_timestamp<'de, D>(deserializer: D) -> Result<i64, D::Error>
@@ -92,10 +111,88 @@ where
     }
 }
 
+fn deserialize_optional_timestamp<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum TimestampFormat {
+        String(String),
+        Integer(i64),
+    }
+
+    // Events without a schedule come back as null, "" or "0"
+    let timestamp = match Option::<TimestampFormat>::deserialize(deserializer)? {
+        Some(TimestampFormat::String(s)) => s.trim().parse::<i64>().ok(),
+        Some(TimestampFormat::Integer(i)) => Some(i),
+        None => None,
+    };
+
+    Ok(timestamp.filter(|&t| t > 0))
+}
+
+fn map_events(events: EventList, lang: &str) -> Vec<NewsItem> {
+    events.list.into_iter().map(|item| {
+        let id = item.id.clone();
+        NewsItem {
+            id: None,
+            external_id: item.id,
+            title: item.name,
+            description: item.desc,
+            created_at: item.create_at,
+            banner: Some(vec![item.banner_url]),
+            url: format!("https://www.hoyolab.com/article/{}", id),
+            news_type: "event".to_string(),
+            lang: lang.to_string(),
+            start_at: item.start,
+            end_at: item.end,
+        }
+    }).collect()
+}
+
+fn map_news_list(news: NewsList, lang: &str, type_name: &str) -> Vec<NewsItem> {
+    news.list.into_iter().map(|item| {
+        let post_id = item.post.post_id.clone();
+        NewsItem {
+            id: None,
+            external_id: item.post.post_id,
+            title: item.post.subject,
+            description: item.post.content,
+            created_at: item.post.created_at,
+            banner: if item.image_list.is_empty() {
+                None
+            } else {
+                Some(item.image_list.into_iter().map(|img| img.url).collect())
+            },
+            url: format!("https://www.hoyolab.com/article/{}", post_id),
+            news_type: type_name.to_string(),
+            lang: lang.to_string(),
+            start_at: None,
+            end_at: None,
+        }
+    }).collect()
+}
+
+impl Default for NewsResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl NewsResolver {
     pub fn new() -> Self {
+        Self::with_host(DEFAULT_HOST)
+    }
+
+    pub fn with_host(host: impl Into<String>) -> Self {
+        Self::with_client(host, HttpClient::default())
+    }
+
+    pub fn with_client(host: impl Into<String>, client: HttpClient) -> Self {
         Self {
-            client: Client::new(),
+            client,
+            host: host.into(),
         }
     }
 
@@ -113,79 +210,134 @@ impl NewsResolver {
     }
 
     async fn fetch_events(&self, lang: &str) -> Result<Vec<NewsItem>, Box<dyn std::error::Error + Send + Sync>> {
-        let response = self.client
-            .get("https://bbs-api-os.hoyolab.com/community/community_contribution/wapi/event/list")
-            .query(&[
-                ("page_size", "15"),
-                ("size", "15"),
-                ("gids", "6"),
-            ])
-            .header("x-rpc-app_version", "2.42.0")
-            .header("x-rpc-client_type", "4")
-            .header("x-rpc-language", lang)
-            .send()
-            .await?;
+        let request = with_api_headers(self.client.get(format!("{}{}", self.host, EVENT_LIST_PATH)))
+            .query(EVENT_LIST_QUERY)
+            .header("x-rpc-language", lang);
+        let response = self.client.send(request).await?;
 
         let data: HoyolabResponse<EventList> = response.json().await?;
-        
-        Ok(data.data.list.into_iter().map(|item| {
-            let id = item.id.clone();
-            NewsItem {
-                id: None,
-                external_id: item.id,
-                title: item.name,
-                description: item.desc,
-                created_at: item.create_at,
-                banner: Some(vec![item.banner_url]),
-                url: format!("https://www.hoyolab.com/article/{}", id),
-                news_type: "event".to_string(),
-                lang: lang.to_string(),
-            }
-        }).collect())
+        Ok(map_events(data.data, lang))
     }
 
     async fn fetch_notices(&self, lang: &str) -> Result<Vec<NewsItem>, Box<dyn std::error::Error + Send + Sync>> {
-        self.fetch_news_type(lang, 1, "notice").await
+        self.fetch_news_type(lang, NOTICE_TYPE, "notice").await
     }
 
     async fn fetch_info(&self, lang: &str) -> Result<Vec<NewsItem>, Box<dyn std::error::Error + Send + Sync>> {
-        self.fetch_news_type(lang, 3, "info").await
+        self.fetch_news_type(lang, INFO_TYPE, "info").await
     }
 
     async fn fetch_news_type(&self, lang: &str, news_type: i32, type_name: &str) 
         -> Result<Vec<NewsItem>, Box<dyn std::error::Error + Send + Sync>> {
-        let response = self.client
-            .get("https://bbs-api-os.hoyolab.com/community/post/wapi/getNewsList")
-            .query(&[
-                ("gids", "6"),
-                ("page_size", "15"),
-                ("type", &news_type.to_string()),
-            ])
-            .header("x-rpc-app_version", "2.42.0")
-            .header("x-rpc-client_type", "4")
-            .header("x-rpc-language", lang)
-            .send()
-            .await?;
+        let request = with_api_headers(self.client.get(format!("{}{}", self.host, NEWS_LIST_PATH)))
+            .query(NEWS_LIST_QUERY)
+            .query(&[("type", news_type)])
+            .header("x-rpc-language", lang);
+        let response = self.client.send(request).await?;
 
         let data: HoyolabResponse<NewsList> = response.json().await?;
-        
-        Ok(data.data.list.into_iter().map(|item| {
-            let post_id = item.post.post_id.clone();
-            NewsItem {
-                id: None,
-                external_id: item.post.post_id,
-                title: item.post.subject,
-                description: item.post.content,
-                created_at: item.post.created_at,
-                banner: if item.image_list.is_empty() {
-                    None
-                } else {
-                    Some(item.image_list.into_iter().map(|img| img.url).collect())
-                },
-                url: format!("https://www.hoyolab.com/article/{}", post_id),
-                news_type: type_name.to_string(),
-                lang: lang.to_string(),
-            }
-        }).collect())
-    }
-} 
\ No newline at end of file
+        Ok(map_news_list(data.data, lang, type_name))
+    }
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_event_schedule() {
+        let json = r#"{
+            "id": "369",
+            "name": "Robin's Rhythmic Festival",
+            "desc": "Welcome!",
+            "create_at": "1715248802",
+            "start": "1715248800",
+            "end": "1720454399",
+            "banner_url": "https://example.com/banner.png"
+        }"#;
+
+        let item: EventItem = serde_json::from_str(json).unwrap();
+        assert_eq!(item.start, Some(1715248800));
+        assert_eq!(item.end, Some(1720454399));
+    }
+
+    #[test]
+    fn test_parse_event_without_schedule() {
+        let json = r#"{
+            "id": "370",
+            "name": "Web Event",
+            "desc": "",
+            "create_at": 1715248802,
+            "start": "0",
+            "banner_url": ""
+        }"#;
+
+        let item: EventItem = serde_json::from_str(json).unwrap();
+        assert_eq!(item.start, None);
+        assert_eq!(item.end, None);
+    }
+
+    #[test]
+    fn test_map_events_fixture() {
+        let data: HoyolabResponse<EventList> = serde_json::from_str(include_str!("fixtures/news_events.json")).unwrap();
+        let events = map_events(data.data, "en-us");
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].external_id, "1125");
+        assert_eq!(events[0].url, "https://www.hoyolab.com/article/1125");
+        assert_eq!(events[0].created_at, 1760313600);
+        assert_eq!(events[0].start_at, Some(1760313600));
+        assert_eq!(events[0].end_at, Some(1762127999));
+        assert_eq!(events[1].start_at, None);
+        assert_eq!(events[1].end_at, None);
+        assert!(events.iter().all(|item| item.news_type == "event" && item.lang == "en-us"));
+    }
+
+    #[test]
+    fn test_map_news_list_fixtures() {
+        let data: HoyolabResponse<NewsList> = serde_json::from_str(include_str!("fixtures/news_notices.json")).unwrap();
+        let notices = map_news_list(data.data, "ja-jp", "notice");
+
+        assert_eq!(notices.len(), 2);
+        assert_eq!(notices[0].title, "Version 3.6 Update Maintenance Preview");
+        assert_eq!(notices[0].banner.as_ref().map(Vec::len), Some(1));
+        assert_eq!(notices[1].created_at, 1760400000);
+        assert!(notices[1].banner.is_none());
+        assert!(notices.iter().all(|item| item.news_type == "notice" && item.lang == "ja-jp"));
+
+        let data: HoyolabResponse<NewsList> = serde_json::from_str(include_str!("fixtures/news_info.json")).unwrap();
+        let info = map_news_list(data.data, "en-us", "info");
+
+        assert_eq!(info.len(), 1);
+        assert_eq!(info[0].url, "https://www.hoyolab.com/article/41240321");
+        assert_eq!(info[0].banner.as_ref().map(Vec::len), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_news_from_configured_host() {
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        use wiremock::matchers::{path, query_param};
+
+        let server = MockServer::start().await;
+        Mock::given(path(EVENT_LIST_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string(include_str!("fixtures/news_events.json")))
+            .mount(&server)
+            .await;
+        Mock::given(path(NEWS_LIST_PATH))
+            .and(query_param("type", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(include_str!("fixtures/news_notices.json")))
+            .mount(&server)
+            .await;
+        Mock::given(path(NEWS_LIST_PATH))
+            .and(query_param("type", "3"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(include_str!("fixtures/news_info.json")))
+            .mount(&server)
+            .await;
+
+        let resolver = NewsResolver::with_host(server.uri());
+        let news = resolver.fetch_news("en-us").await.unwrap();
+
+        let types: Vec<&str> = news.iter().map(|item| item.news_type.as_str()).collect();
+        assert_eq!(types, vec!["event", "event", "notice", "notice", "info"]);
+    }
+}
@@ -1,20 +1,32 @@
 use async_trait::async_trait;
 use log::{debug, error};
-use reqwest::Client;
 use scraper::{Html, Selector};
 use mongodb::bson::DateTime;
 use regex::Regex;
 
-use super::{CodeResolver, RedemptionCode};
+use super::{CodeResolver, RedemptionCode, Server};
+use crate::utils::http::HttpClient;
+
+pub const DEFAULT_BASE_URL: &str = "https://www.polygon.com/honkai-star-rail-guides/23699079/code-redeem-redemption-gift-stellar-jade";
 
 pub struct PolygonResolver {
-    client: Client,
+    client: HttpClient,
+    base_url: String,
 }
 
 impl PolygonResolver {
     pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self::with_client(base_url, HttpClient::default())
+    }
+
+    pub fn with_client(base_url: impl Into<String>, client: HttpClient) -> Self {
         Self {
-            client: Client::new(),
+            client,
+            base_url: base_url.into(),
         }
     }
 
@@ -65,9 +77,17 @@ impl PolygonResolver {
                             id: None,
                             code,
                             rewards,
+                            items: Vec::new(),
                             source: "Polygon".to_string(),
+                            sources: Vec::new(),
                             date: Some(DateTime::now()),
                             active: true,
+                            first_seen_at: None,
+                            last_seen_in_source_at: None,
+                            last_validated_at: None,
+                            deactivated_at: None,
+                            server: Server::Global,
+                            regions: Vec::new(),
                         });
                     }
                 }
@@ -79,6 +99,12 @@ impl PolygonResolver {
     }
 }
 
+impl Default for PolygonResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl CodeResolver for PolygonResolver {
     fn name(&self) -> String {
@@ -86,17 +112,13 @@ impl CodeResolver for PolygonResolver {
     }
 
     fn base_url(&self) -> String {
-        "https://www.polygon.com/honkai-star-rail-guides/23699079/code-redeem-redemption-gift-stellar-jade".to_string()
+        self.base_url.clone()
     }
 
     async fn fetch_codes(&self) -> Result<Vec<RedemptionCode>, Box<dyn std::error::Error + Send + Sync>> {
         debug!("Fetching codes from Polygon");
         
-        let response = self.client
-            .get(self.base_url())
-            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36")
-            .send()
-            .await?;
+        let response = self.client.send(self.client.get(self.base_url())).await?;
 
         if !response.status().is_success() {
             error!("Failed to fetch Polygon page: {}", response.status());
@@ -109,4 +131,5 @@ impl CodeResolver for PolygonResolver {
 }
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests; 
\ No newline at end of file
@@ -1,19 +1,31 @@
 use async_trait::async_trait;
 use log::{debug, error};
-use reqwest::Client;
 use scraper::{Html, Selector};
 use mongodb::bson::DateTime;
 
-use super::{CodeResolver, RedemptionCode};
+use super::{CodeResolver, RedemptionCode, Server};
+use crate::utils::http::HttpClient;
+
+pub const DEFAULT_BASE_URL: &str = "https://www.prydwen.gg/star-rail/";
 
 pub struct PrydwenResolver {
-    client: Client,
+    client: HttpClient,
+    base_url: String,
 }
 
 impl PrydwenResolver {
     pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self::with_client(base_url, HttpClient::default())
+    }
+
+    pub fn with_client(base_url: impl Into<String>, client: HttpClient) -> Self {
         Self {
-            client: Client::new(),
+            client,
+            base_url: base_url.into(),
         }
     }
 
@@ -50,9 +62,17 @@ impl PrydwenResolver {
                             id: None,
                             code,
                             rewards,
+                            items: Vec::new(),
                             source: "Prydwen".to_string(),
+                            sources: Vec::new(),
                             date: Some(DateTime::now()),
                             active: true,
+                            first_seen_at: None,
+                            last_seen_in_source_at: None,
+                            last_validated_at: None,
+                            deactivated_at: None,
+                            server: Server::Global,
+                            regions: Vec::new(),
                         });
                     }
                 }
@@ -64,6 +84,12 @@ impl PrydwenResolver {
     }
 }
 
+impl Default for PrydwenResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl CodeResolver for PrydwenResolver {
     fn name(&self) -> String {
@@ -71,17 +97,13 @@ impl CodeResolver for PrydwenResolver {
     }
 
     fn base_url(&self) -> String {
-        "https://www.prydwen.gg/star-rail/".to_string()
+        self.base_url.clone()
     }
 
     async fn fetch_codes(&self) -> Result<Vec<RedemptionCode>, Box<dyn std::error::Error + Send + Sync>> {
         debug!("Fetching codes from Prydwen");
         
-        let response = self.client
-            .get(self.base_url())
-            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36")
-            .send()
-            .await?;
+        let response = self.client.send(self.client.get(self.base_url())).await?;
 
         if !response.status().is_success() {
             error!("Failed to fetch Prydwen page: {}", response.status());
@@ -94,4 +116,5 @@ impl CodeResolver for PrydwenResolver {
 }
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests; 
\ No newline at end of file
@@ -0,0 +1,112 @@
+pub struct CatalogItem {
+    pub id: &'static str,
+    pub name: &'static str,
+    // Normalized spellings used by the different sources (lowercase, ASCII apostrophes, singular)
+    aliases: &'static [&'static str],
+}
+
+pub const CATALOG: &[CatalogItem] = &[
+    CatalogItem {
+        id: "stellar_jade",
+        name: "Stellar Jade",
+        aliases: &["stellar jade", "jade"],
+    },
+    CatalogItem {
+        id: "credit",
+        name: "Credit",
+        aliases: &["credit"],
+    },
+    CatalogItem {
+        id: "travel_encounters",
+        name: "Travel Encounters",
+        aliases: &["travel encounters", "travel encounter"],
+    },
+    CatalogItem {
+        id: "adventure_log",
+        name: "Adventure Log",
+        aliases: &["adventure log"],
+    },
+    CatalogItem {
+        id: "travelers_guide",
+        name: "Traveler's Guide",
+        aliases: &["traveler's guide", "travelers guide", "traveller's guide", "travellers guide"],
+    },
+    CatalogItem {
+        id: "refined_aether",
+        name: "Refined Aether",
+        aliases: &["refined aether"],
+    },
+    CatalogItem {
+        id: "condensed_aether",
+        name: "Condensed Aether",
+        aliases: &["condensed aether"],
+    },
+    CatalogItem {
+        id: "sparse_aether",
+        name: "Sparse Aether",
+        aliases: &["sparse aether"],
+    },
+    CatalogItem {
+        id: "lost_crystal",
+        name: "Lost Crystal",
+        aliases: &["lost crystal"],
+    },
+    CatalogItem {
+        id: "lost_lightdust",
+        name: "Lost Lightdust",
+        aliases: &["lost lightdust", "lost light dust"],
+    },
+    CatalogItem {
+        id: "bottled_soda",
+        name: "Bottled Soda",
+        aliases: &["bottled soda"],
+    },
+    CatalogItem {
+        id: "cosmic_fried_rice",
+        name: "Cosmic Fried Rice",
+        aliases: &["cosmic fried rice"],
+    },
+    CatalogItem {
+        id: "star_rail_pass",
+        name: "Star Rail Pass",
+        aliases: &["star rail pass"],
+    },
+    CatalogItem {
+        id: "star_rail_special_pass",
+        name: "Star Rail Special Pass",
+        aliases: &["star rail special pass"],
+    },
+    CatalogItem {
+        id: "trailblaze_exp",
+        name: "Trailblaze EXP",
+        aliases: &["trailblaze exp"],
+    },
+    CatalogItem {
+        id: "fuel",
+        name: "Fuel",
+        aliases: &["fuel"],
+    },
+];
+
+pub fn find_by_id(id: &str) -> Option<&'static CatalogItem> {
+    CATALOG.iter().find(|item| item.id == id)
+}
+
+pub fn find_by_name(name: &str) -> Option<&'static CatalogItem> {
+    let normalized = normalize_name(name);
+    let singular = normalized.strip_suffix('s').unwrap_or(&normalized);
+
+    CATALOG.iter().find(|item| {
+        item.aliases
+            .iter()
+            .any(|alias| *alias == normalized || *alias == singular)
+    })
+}
+
+fn normalize_name(name: &str) -> String {
+    name.replace(['’', '`'], "'")
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,114 @@
+/// Languages with translated item names, in the order of the names in `LOCALIZED_NAMES`.
+pub const LOCALIZED_LANGUAGES: &[&str] = &[
+    "en-us", "zh-cn", "zh-tw", "de-de", "es-es", "fr-fr", "id-id",
+    "it-it", "ja-jp", "ko-kr", "pt-pt", "ru-ru", "th-th", "tr-tr", "vi-vn",
+];
+
+const LOCALIZED_NAMES: &[(&str, [&str; 15])] = &[
+    ("stellar_jade", [
+        "Stellar Jade", "星琼", "星瓊", "Stellarjade", "Jade estelar", "Jade stellaire", "Giok Bintang",
+        "Giada stellare", "星玉", "성옥", "Jade Estelar", "Звёздный нефрит", "หยกดารา", "Yıldız Yeşimi", "Ngọc Sao",
+    ]),
+    ("credit", [
+        "Credit", "信用点", "信用點", "Credits", "Créditos", "Crédits", "Kredit",
+        "Crediti", "信用ポイント", "크레딧", "Créditos", "Кредиты", "เครดิต", "Kredi", "Tín Dụng",
+    ]),
+    ("travel_encounters", [
+        "Travel Encounters", "旅情见闻", "旅情見聞", "Reiseerlebnisse", "Encuentros de viaje", "Rencontres de voyage", "Pertemuan Perjalanan",
+        "Incontri di viaggio", "旅の見聞", "여행 견문", "Encontros de Viagem", "Дорожные встречи", "การเผชิญหน้าระหว่างเดินทาง", "Seyahat Karşılaşmaları", "Gặp Gỡ Khi Du Hành",
+    ]),
+    ("adventure_log", [
+        "Adventure Log", "冒险记录", "冒險紀錄", "Abenteuerlogbuch", "Registro de aventuras", "Journal d'aventure", "Catatan Petualangan",
+        "Diario d'avventura", "冒険記録", "모험 기록", "Registro de Aventura", "Журнал приключений", "บันทึกการผจญภัย", "Macera Günlüğü", "Nhật Ký Phiêu Lưu",
+    ]),
+    ("travelers_guide", [
+        "Traveler's Guide", "漫游指南", "漫遊指南", "Reiseführer", "Guía del viajero", "Guide du voyageur", "Panduan Penjelajah",
+        "Guida del viaggiatore", "漫遊指南", "여행 가이드", "Guia do Viajante", "Путеводитель", "คู่มือนักเดินทาง", "Gezgin Rehberi", "Chỉ Nam Du Hành",
+    ]),
+    ("refined_aether", [
+        "Refined Aether", "提纯以太", "提純以太", "Raffinierter Äther", "Éter refinado", "Éther raffiné", "Ether Murni",
+        "Etere raffinato", "精製エーテル", "정제된 에테르", "Éter Refinado", "Очищенный эфир", "อีเธอร์บริสุทธิ์", "Arıtılmış Eter", "Ether Tinh Luyện",
+    ]),
+    ("condensed_aether", [
+        "Condensed Aether", "凝缩以太", "凝縮以太", "Kondensierter Äther", "Éter condensado", "Éther condensé", "Ether Padat",
+        "Etere condensato", "凝縮エーテル", "응축된 에테르", "Éter Condensado", "Конденсированный эфир", "อีเธอร์เข้มข้น", "Yoğunlaştırılmış Eter", "Ether Ngưng Tụ",
+    ]),
+    ("sparse_aether", [
+        "Sparse Aether", "稀薄以太", "稀薄以太", "Dünner Äther", "Éter disperso", "Éther dispersé", "Ether Tipis",
+        "Etere rarefatto", "希薄エーテル", "희박한 에테르", "Éter Esparso", "Разреженный эфир", "อีเธอร์เจือจาง", "Seyrek Eter", "Ether Loãng",
+    ]),
+    ("lost_crystal", [
+        "Lost Crystal", "遗失晶块", "遺失晶塊", "Verlorener Kristall", "Cristal perdido", "Cristal perdu", "Kristal yang Hilang",
+        "Cristallo perduto", "遺失の結晶", "잃어버린 결정", "Cristal Perdido", "Утерянный кристалл", "คริสตัลที่สูญหาย", "Kayıp Kristal", "Tinh Thể Thất Lạc",
+    ]),
+    ("lost_lightdust", [
+        "Lost Lightdust", "遗失碎星", "遺失碎星", "Verlorener Lichtstaub", "Polvo de luz perdido", "Poussière de lumière perdue", "Debu Cahaya yang Hilang",
+        "Polvere di luce perduta", "遺失の星屑", "잃어버린 별가루", "Pó de Luz Perdido", "Утерянная световая пыль", "ผงแสงที่สูญหาย", "Kayıp Işık Tozu", "Bụi Sáng Thất Lạc",
+    ]),
+    ("bottled_soda", [
+        "Bottled Soda", "罐装快乐水", "罐裝快樂水", "Flaschenlimo", "Refresco embotellado", "Soda en bouteille", "Soda Botol",
+        "Bibita in bottiglia", "缶入りカコカーラ", "캔 탄산음료", "Refrigerante Engarrafado", "Газировка в бутылке", "โซดาขวด", "Şişe Gazoz", "Nước Ngọt Đóng Chai",
+    ]),
+    ("cosmic_fried_rice", [
+        "Cosmic Fried Rice", "宇宙炒饭", "宇宙炒飯", "Kosmischer Bratreis", "Arroz frito cósmico", "Riz frit cosmique", "Nasi Goreng Kosmik",
+        "Riso fritto cosmico", "宇宙チャーハン", "우주 볶음밥", "Arroz Frito Cósmico", "Космический жареный рис", "ข้าวผัดจักรวาล", "Kozmik Kızarmış Pilav", "Cơm Chiên Vũ Trụ",
+    ]),
+    ("star_rail_pass", [
+        "Star Rail Pass", "星轨通票", "星軌通票", "Sternenschienenpass", "Pase Estelar", "Passe stellaire", "Tiket Star Rail",
+        "Pass Star Rail", "星の軌道パス", "스타레일 패스", "Passe Estelar", "Звёздный проездной", "บัตร Star Rail", "Yıldız Rayı Bileti", "Vé Tàu Sao",
+    ]),
+    ("star_rail_special_pass", [
+        "Star Rail Special Pass", "星轨专票", "星軌專票", "Sternenschienen-Sonderpass", "Pase Estelar Especial", "Passe stellaire spécial", "Tiket Khusus Star Rail",
+        "Pass speciale Star Rail", "星の軌道専用チケット", "스타레일 전용 패스", "Passe Estelar Especial", "Особый звёздный проездной", "บัตรพิเศษ Star Rail", "Özel Yıldız Rayı Bileti", "Vé Tàu Sao Đặc Biệt",
+    ]),
+    ("trailblaze_exp", [
+        "Trailblaze EXP", "开拓经验", "開拓經驗", "Pionier-EP", "EXP de Trazacaminos", "EXP de pionnier", "EXP Trailblaze",
+        "EXP da Pioniere", "開拓経験", "개척 경험치", "EXP de Desbravador", "Опыт Освоения", "EXP บุกเบิก", "Öncü TP", "EXP Khai Phá",
+    ]),
+    ("fuel", [
+        "Fuel", "燃料", "燃料", "Treibstoff", "Combustible", "Carburant", "Bahan Bakar",
+        "Carburante", "燃料", "연료", "Combustível", "Топливо", "เชื้อเพลิง", "Yakıt", "Nhiên Liệu",
+    ]),
+];
+
+/// The name of `item_id` in `lang`, or `None` for unknown items and languages without
+/// translated names.
+pub fn localized_name(item_id: &str, lang: &str) -> Option<&'static str> {
+    let lang_index = LOCALIZED_LANGUAGES.iter().position(|&supported| supported == lang)?;
+
+    LOCALIZED_NAMES
+        .iter()
+        .find(|(id, _)| *id == item_id)
+        .map(|(_, names)| names[lang_index])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rewards::catalog::CATALOG;
+    use crate::utils::lang_parser::SUPPORTED_LANGUAGES;
+
+    #[test]
+    fn test_every_catalog_item_is_localized() {
+        for item in CATALOG {
+            assert!(LOCALIZED_NAMES.iter().any(|(id, _)| *id == item.id), "Missing names for {}", item.id);
+            assert_eq!(localized_name(item.id, "en-us"), Some(item.name));
+        }
+    }
+
+    #[test]
+    fn test_localized_name_lookup() {
+        let stellar_jade = [
+            ("en-us", "Stellar Jade"), ("zh-cn", "星琼"), ("zh-tw", "星瓊"), ("de-de", "Stellarjade"),
+            ("es-es", "Jade estelar"), ("fr-fr", "Jade stellaire"), ("id-id", "Giok Bintang"), ("it-it", "Giada stellare"),
+            ("ja-jp", "星玉"), ("ko-kr", "성옥"), ("pt-pt", "Jade Estelar"), ("ru-ru", "Звёздный нефрит"),
+            ("th-th", "หยกดารา"), ("tr-tr", "Yıldız Yeşimi"), ("vi-vn", "Ngọc Sao"),
+        ];
+        assert_eq!(LOCALIZED_LANGUAGES, SUPPORTED_LANGUAGES);
+        for (lang, name) in stellar_jade {
+            assert_eq!(localized_name("stellar_jade", lang), Some(name), "{}", lang);
+        }
+        assert_eq!(localized_name("credit", "xx-xx"), None);
+        assert_eq!(localized_name("unknown_item", "en-us"), None);
+    }
+}
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use regex::Regex;
+use std::sync::LazyLock;
+
+pub mod catalog;
+pub mod locale;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Reward {
+    pub item_id: Option<String>,
+    pub item_name: String,
+    pub amount: Option<u32>,
+}
+
+// "x50 Stellar Jade", "50 Stellar Jades", "10,000 Credit", "10k Credits"
+static AMOUNT_FIRST: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^[x×]?\s*(\d[\d,.]*)\s*(k)?\s+(.+)$").unwrap()
+});
+
+// "Stellar Jade x50", "Credit ×10,000", "Adventure Log x2"
+static AMOUNT_LAST: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(.+?)\s*[x×]\s*(\d[\d,.]*)\s*(k)?$").unwrap()
+});
+
+// "Stellar Jade 50"
+static AMOUNT_LAST_BARE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(.+?)\s+(\d[\d,.]*)\s*(k)?$").unwrap()
+});
+
+pub fn parse_rewards(raw_rewards: &[String]) -> Vec<Reward> {
+    raw_rewards
+        .iter()
+        .map(|raw| parse_reward(raw))
+        .filter(|reward| !reward.item_name.is_empty())
+        .collect()
+}
+
+pub fn parse_reward(raw: &str) -> Reward {
+    let cleaned = raw
+        .replace("(new!)", "")
+        .replace(['(', ')'], " ");
+    let cleaned = cleaned.trim();
+
+    let (name, amount) = if let Some(captures) = AMOUNT_FIRST.captures(cleaned) {
+        (captures[3].to_string(), parse_amount(&captures[1], captures.get(2).is_some()))
+    } else if let Some(captures) = AMOUNT_LAST.captures(cleaned) {
+        (captures[1].to_string(), parse_amount(&captures[2], captures.get(3).is_some()))
+    } else if let Some(captures) = AMOUNT_LAST_BARE.captures(cleaned) {
+        (captures[1].to_string(), parse_amount(&captures[2], captures.get(3).is_some()))
+    } else {
+        (cleaned.to_string(), None)
+    };
+
+    let name = name.trim_matches(|c: char| c.is_whitespace() || matches!(c, '-' | '—' | ':' | ','));
+
+    match catalog::find_by_name(name) {
+        Some(item) => Reward {
+            item_id: Some(item.id.to_string()),
+            item_name: item.name.to_string(),
+            amount,
+        },
+        None => Reward {
+            item_id: None,
+            item_name: name.to_string(),
+            amount,
+        },
+    }
+}
+
+fn parse_amount(digits: &str, thousands: bool) -> Option<u32> {
+    if thousands {
+        let value: f64 = digits.replace(',', ".").parse().ok()?;
+        return Some((value * 1000.0).round() as u32);
+    }
+
+    digits.replace([',', '.'], "").parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reward(item_id: &str, item_name: &str, amount: u32) -> Reward {
+        Reward {
+            item_id: Some(item_id.to_string()),
+            item_name: item_name.to_string(),
+            amount: Some(amount),
+        }
+    }
+
+    #[test]
+    fn test_amount_first_formats() {
+        assert_eq!(parse_reward("x50 Stellar Jade"), reward("stellar_jade", "Stellar Jade", 50));
+        assert_eq!(parse_reward("50 Stellar Jades"), reward("stellar_jade", "Stellar Jade", 50));
+        assert_eq!(parse_reward("10,000 Credit"), reward("credit", "Credit", 10000));
+        assert_eq!(parse_reward("x10,000 Credits"), reward("credit", "Credit", 10000));
+        assert_eq!(parse_reward("10k Credits"), reward("credit", "Credit", 10000));
+        assert_eq!(parse_reward("x2 Traveler's Guide"), reward("travelers_guide", "Traveler's Guide", 2));
+    }
+
+    #[test]
+    fn test_amount_last_formats() {
+        assert_eq!(parse_reward("Stellar Jade x50"), reward("stellar_jade", "Stellar Jade", 50));
+        assert_eq!(parse_reward("Credit x10,000"), reward("credit", "Credit", 10000));
+        assert_eq!(parse_reward("Credits ×10000"), reward("credit", "Credit", 10000));
+        assert_eq!(parse_reward("Adventure Log x2"), reward("adventure_log", "Adventure Log", 2));
+        assert_eq!(parse_reward("Traveler’s Guide 3"), reward("travelers_guide", "Traveler's Guide", 3));
+    }
+
+    #[test]
+    fn test_unknown_and_missing_amount() {
+        assert_eq!(parse_reward("5 Mystery Box"), Reward {
+            item_id: None,
+            item_name: "Mystery Box".to_string(),
+            amount: Some(5),
+        });
+        assert_eq!(parse_reward("Stellar Jade (new!)"), Reward {
+            item_id: Some("stellar_jade".to_string()),
+            item_name: "Stellar Jade".to_string(),
+            amount: None,
+        });
+    }
+
+    #[test]
+    fn test_parse_rewards_skips_empty() {
+        let raw = vec!["60 Stellar Jade".to_string(), " ".to_string(), "Credit x5000".to_string()];
+        let rewards = parse_rewards(&raw);
+        assert_eq!(rewards.len(), 2);
+        assert_eq!(rewards[1], reward("credit", "Credit", 5000));
+    }
+}
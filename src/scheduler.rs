@@ -1,8 +1,54 @@
 use log::{info, debug, error};
 use tokio_cron_scheduler::{JobScheduler, Job};
 use crate::services::code_service::CodeService;
+use crate::services::health::{self, Component};
+use crate::services::metrics;
 use crate::services::news_service::NewsService;
+use crate::services::response_cache;
+use crate::services::validation_queue;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often the validation worker looks at the queue while no code is ready, on top of
+/// being woken when codes are queued.
+const VALIDATION_IDLE_POLL: Duration = Duration::from_secs(30);
+
+fn record_job(job: &str, outcome: Result<(), String>, started: Instant) {
+    metrics::instance().observe_job(job, outcome.is_ok(), started.elapsed());
+    match outcome {
+        Ok(()) => health::instance().record_success(Component::Job, job),
+        Err(e) => health::instance().record_failure(Component::Job, job, &e),
+    }
+}
+
+/// Validates queued codes one at a time for as long as the server runs. Requests are paced by
+/// the redeem budget, so the worker only sleeps while nothing in the queue is ready.
+fn spawn_validation_worker() {
+    tokio::spawn(async move {
+        let code_service = loop {
+            match CodeService::new().await {
+                Ok(code_service) => break code_service,
+                Err(e) => {
+                    error!("Failed to initialize code service for the validation queue: {}", e);
+                    tokio::time::sleep(VALIDATION_IDLE_POLL).await;
+                }
+            }
+        };
+
+        loop {
+            let started = Instant::now();
+            match code_service.validate_next().await {
+                Ok(true) => record_job("validate_code", Ok(()), started),
+                Ok(false) => validation_queue::wait(VALIDATION_IDLE_POLL).await,
+                Err(e) => {
+                    error!("Failed to validate queued code: {}", e);
+                    record_job("validate_code", Err(e.to_string()), started);
+                    validation_queue::wait(VALIDATION_IDLE_POLL).await;
+                }
+            }
+        }
+    });
+}
 
 pub async fn init_scheduler() -> Result<(), Box<dyn std::error::Error>> {
     info!("Initializing scheduler");
@@ -11,65 +57,102 @@ pub async fn init_scheduler() -> Result<(), Box<dyn std::error::Error>> {
     sched.add(Job::new_async("0 * * * * *", move |_, _| {
         Box::pin(async move {
             info!("Running scheduled code scraping");
-            match CodeService::new().await {
+            let started = Instant::now();
+            let outcome = match CodeService::new().await {
                 Ok(code_service) => {
                     let code_service = Arc::new(code_service);
-                    match code_service.get_all_codes().await {
+                    let outcome = match code_service.get_all_codes().await {
                         Ok((active, inactive)) => {
                             debug!(
                                 "Scheduled scraping completed. Found {} active and {} inactive codes",
                                 active.len(),
                                 inactive.len()
                             );
+                            Ok(())
                         },
-                        Err(e) => error!("Failed to fetch codes in scheduled job: {}", e)
-                    }
+                        Err(e) => {
+                            error!("Failed to fetch codes in scheduled job: {}", e);
+                            Err(e.to_string())
+                        }
+                    };
+
+                    let stats = response_cache::instance().stats();
+                    debug!("Response cache: {} hits, {} misses", stats.hits, stats.misses);
+                    outcome
                 },
-                Err(e) => error!("Failed to initialize code service in scheduled job: {}", e)
-            }
+                Err(e) => {
+                    error!("Failed to initialize code service in scheduled job: {}", e);
+                    Err(e.to_string())
+                }
+            };
+            record_job("scrape_codes", outcome, started);
         })
     })?).await?;
 
     sched.add(Job::new_async("0 */30 * * * *", move |_, _| {
         Box::pin(async move {
-            info!("Running scheduled code validation");
-            match CodeService::new().await {
+            info!("Queueing stale codes for revalidation");
+            let started = Instant::now();
+            let outcome = match CodeService::new().await {
                 Ok(code_service) => {
-                    match code_service.validate_active_codes().await {
-                        Ok(_) => {
-                            info!("Scheduled code validation completed successfully");
+                    match code_service.queue_revalidations().await {
+                        Ok(queued) => {
+                            info!("Queued {} codes for revalidation", queued);
+                            Ok(())
                         },
-                        Err(e) => error!("Failed to validate codes in scheduled job: {}", e)
+                        Err(e) => {
+                            error!("Failed to queue codes for revalidation: {}", e);
+                            Err(e.to_string())
+                        }
                     }
                 },
-                Err(e) => error!("Failed to initialize code service for validation job: {}", e)
-            }
+                Err(e) => {
+                    error!("Failed to initialize code service for revalidation job: {}", e);
+                    Err(e.to_string())
+                }
+            };
+            record_job("queue_revalidations", outcome, started);
         })
     })?).await?;
 
     sched.add(Job::new_async("0 */15 * * * *", move |_, _| {
         Box::pin(async move {
             info!("Running scheduled news fetch");
-            match NewsService::new().await {
+            let started = Instant::now();
+            let outcome = match NewsService::new().await {
                 Ok(news_service) => {
                     match news_service.fetch_all_news().await {
                         Ok(news) => {
                             match news_service.save_news(&news).await {
-                                Ok(_) => info!("Successfully updated {} news items", news.len()),
-                                Err(e) => error!("Failed to save news items: {}", e)
+                                Ok(_) => {
+                                    info!("Successfully updated {} news items", news.len());
+                                    Ok(())
+                                },
+                                Err(e) => {
+                                    error!("Failed to save news items: {}", e);
+                                    Err(e.to_string())
+                                }
                             }
                         },
-                        Err(e) => error!("Failed to fetch news in scheduled job: {}", e)
+                        Err(e) => {
+                            error!("Failed to fetch news in scheduled job: {}", e);
+                            Err(e.to_string())
+                        }
                     }
                 },
-                Err(e) => error!("Failed to initialize news service in scheduled job: {}", e)
-            }
+                Err(e) => {
+                    error!("Failed to initialize news service in scheduled job: {}", e);
+                    Err(e.to_string())
+                }
+            };
+            record_job("fetch_news", outcome, started);
         })
     })?).await?;
 
     sched.start().await?;
+    spawn_validation_worker();
 
     info!("Scheduler started successfully");
 
     Ok(())
-} 
\ No newline at end of file
+}
\ No newline at end of file
@@ -1,146 +1,598 @@
 use log::{info, error, warn, debug};
-use crate::resolvers::{CodeResolver, RedemptionCode};
-use super::db_service::DbService;
+use crate::config::{Settings, ValidatorConfig};
+use crate::resolvers::{CodeResolver, CodeSource, RedemptionCode, RegionStatus};
+use crate::resolvers::html::HtmlResolver;
+use crate::rewards;
+use super::storage::{self, Storage};
+use super::validation_queue::{self, QueuedValidation, ValidationPriority};
+use super::validator_pool::{self, AccountPool};
 use super::validator_service::{ValidatorService, ValidationResult};
+use super::data_version::{self, Dataset};
+use super::drift_detector;
+use super::event_bus;
+use super::health::{self, Component};
+use super::metrics;
+use super::source_tracker;
+use super::notification_service::{code_summary, CodeEvent, NotificationService};
 use std::sync::Arc;
 use std::collections::HashMap;
+use std::time::Instant;
+use mongodb::bson::DateTime;
 
 pub struct CodeService {
     resolvers: Vec<Arc<dyn CodeResolver>>,
-    db_service: Arc<DbService>,
+    storage: Arc<dyn Storage>,
     validator: ValidatorService,
+    notifier: NotificationService,
+    validator_config: ValidatorConfig,
 }
 
 impl CodeService {
-    pub async fn new() -> Result<Self, mongodb::error::Error> {
-        let resolvers: Vec<Arc<dyn CodeResolver>> = vec![
-            Arc::new(crate::resolvers::eurogamer::EurogamerResolver::new()),
-            Arc::new(crate::resolvers::game8::Game8Resolver::new()),
-            Arc::new(crate::resolvers::fandom::FandomResolver::new()),
-            Arc::new(crate::resolvers::polygon::PolygonResolver::new()),
-            Arc::new(crate::resolvers::prydwen::PrydwenResolver::new()),
-            Arc::new(crate::resolvers::hoyolab::HoyolabResolver::new()),
+    pub async fn new() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let config = Settings::new()?;
+        let storage = storage::instance().await;
+
+        Ok(Self::with_storage(config, storage, validator_pool::instance()))
+    }
+
+    /// Builds the service against the given storage and validator account pool, scraping and
+    /// validating through the upstream endpoints in `config`.
+    pub fn with_storage(config: Settings, storage: Arc<dyn Storage>, pool: Arc<AccountPool>) -> Self {
+        let upstream = &config.upstream;
+        let http = &config.http;
+        let mut resolvers: Vec<Arc<dyn CodeResolver>> = vec![
+            Arc::new(crate::resolvers::eurogamer::EurogamerResolver::with_client(&upstream.eurogamer, http.client("eurogamer"))),
+            Arc::new(crate::resolvers::game8::Game8Resolver::with_client(&upstream.game8, http.client("game8"))),
+            Arc::new(crate::resolvers::fandom::FandomResolver::with_client(&upstream.fandom, http.client("fandom"))),
+            Arc::new(crate::resolvers::polygon::PolygonResolver::with_client(&upstream.polygon, http.client("polygon"))),
+            Arc::new(crate::resolvers::prydwen::PrydwenResolver::with_client(&upstream.prydwen, http.client("prydwen"))),
+            Arc::new(crate::resolvers::hoyolab::HoyolabResolver::with_client(&upstream.hoyolab, http.client("hoyolab"))),
         ];
-        
-        let db_service = DbService::instance().await;
-        let validator = ValidatorService::new();
-        
-        Ok(Self { resolvers, db_service, validator })
+        for definition in &config.sources {
+            if resolvers.iter().any(|resolver| resolver.name() == definition.name) {
+                warn!("Skipping source {}: a resolver with that name already exists", definition.name);
+                continue;
+            }
+            match HtmlResolver::with_client(definition.clone(), http.client(&definition.name)) {
+                Ok(resolver) => resolvers.push(Arc::new(resolver)),
+                Err(e) => error!("Skipping source {}: {}", definition.name, e),
+            }
+        }
+        for resolver in &resolvers {
+            source_tracker::instance().register(&resolver.name());
+        }
+        let notifier = NotificationService::new(config.webhooks.clone(), storage.clone());
+        let validator_config = config.validator.clone();
+        let validator = ValidatorService::new(config, storage.clone()).with_pool(pool);
+
+        Self {
+            resolvers,
+            storage,
+            validator,
+            notifier,
+            validator_config,
+        }
     }
 
     pub async fn get_all_codes(&self) -> Result<(Vec<RedemptionCode>, Vec<RedemptionCode>), Box<dyn std::error::Error + Send + Sync>> {
         let mut all_codes = Vec::new();
         
-        let (existing_active, existing_inactive) = self.db_service.get_codes().await?;
+        let (existing_active, existing_inactive) = self.storage.get_codes().await?;
         let existing_codes: std::collections::HashMap<String, bool> = existing_active
             .iter()
             .map(|code| (code.code.clone(), true))
             .chain(existing_inactive.iter().map(|code| (code.code.clone(), false)))
             .collect();
         
+        let tracker = source_tracker::instance();
         for resolver in &self.resolvers {
+            let name = resolver.name();
+            if !tracker.allow(&name) {
+                debug!("Skipping {}, its circuit breaker is open", name);
+                continue;
+            }
+
+            let started = Instant::now();
             match resolver.fetch_codes().await {
                 Ok(codes) => {
-                    debug!("Successfully retrieved codes from {}", resolver.name());
+                    debug!("Successfully retrieved codes from {}", name);
+                    metrics::instance().observe_scrape(&name, Some(codes.len()), started.elapsed());
+                    health::instance().record_success(Component::Resolver, &name);
+                    tracker.record(&name, Ok(codes.len()));
+                    if let Some(report) = drift_detector::instance().check(&name, &codes) {
+                        for anomaly in &report.anomalies {
+                            warn!("Output of {} looks wrong: {}", name, anomaly.describe());
+                            metrics::instance().drift_anomalies.with_label_values(&[name.as_str(), anomaly.kind()]).inc();
+                        }
+                        self.notifier.notify_drift(&report);
+                    }
                     all_codes.extend(codes);
                 },
                 Err(e) => {
-                    error!("Failed to fetch codes from {}: {}", resolver.name(), e);
+                    error!("Failed to fetch codes from {}: {}", name, e);
+                    metrics::instance().observe_scrape(&name, None, started.elapsed());
+                    health::instance().record_failure(Component::Resolver, &name, &e.to_string());
+                    tracker.record(&name, Err(&e.to_string()));
                 }
             }
         }
         
         // Use HashMap for O(1) deduplication while keeping the last occurrence of each code
+        // and a sighting for every source that reported it
+        let seen_at = DateTime::now();
         let mut unique_codes: std::collections::HashMap<String, RedemptionCode> = HashMap::with_capacity(all_codes.len());
-        for code in all_codes {
+        for mut code in all_codes {
+            let sighting = CodeSource::new(&code.source, &code.rewards, seen_at);
+            if let Some(previous) = unique_codes.remove(&code.code) {
+                code.sources = previous.sources;
+            }
+            code.record_source(sighting);
             unique_codes.insert(code.code.clone(), code);
         }
         
         // Convert back to Vec
         let mut all_codes: Vec<RedemptionCode> = unique_codes.into_values().collect();
+        let mut added = Vec::new();
         
         for code in &mut all_codes {
+            // Resolvers that know the exact items (e.g. Hoyolab icons) fill these in themselves
+            if code.items.is_empty() {
+                code.items = rewards::parse_rewards(&code.rewards);
+            }
+
             if !existing_codes.contains_key(&code.code) {
+                // Active until the validation queue gets to it
                 info!("New code found: {}", code.code);
-                match self.validator.validate_code(&code).await {
-                    Ok(validation_result) => {
-                        match validation_result {
-                            ValidationResult::Valid | ValidationResult::AlreadyRedeemed => {
-                                debug!("New code {} is valid", code.code);
-                                code.active = true;
-                            },
-                            ValidationResult::Expired | ValidationResult::Invalid | ValidationResult::MaxUsageReached => {
-                                debug!("New code {} is invalid", code.code);
-                                code.active = false;
-                            },
-                            ValidationResult::Cooldown => {
-                                warn!("New code {} is in cooldown, marking as active", code.code);
-                                code.active = true;
-                            },
-                            ValidationResult::InvalidCredentials => {
-                                error!("Invalid account credentials during validation");
-                                return Err("Invalid account credentials".into());
-                            },
-                            ValidationResult::Unknown(code_num, message) => {
-                                warn!("Unknown validation result for code {}: {} - {}", code.code, code_num, message);
-                                code.active = true; // Assume active until proven otherwise
-                            }
-                        }
-                        // Add delay between validations to avoid rate limiting
-                        tokio::time::sleep(tokio::time::Duration::from_secs(6)).await;
-                    },
-                    Err(e) => {
-                        error!("Failed to validate new code {}: {}", code.code, e);
-                        code.active = true; // Assume active if validation fails
-                    }
-                }
+                code.active = true;
+                self.notifier.notify(&[CodeEvent::Discovered], code);
+                added.push(code_summary(code));
             } else {
                 code.active = *existing_codes.get(&code.code).unwrap();
             }
         }
         
-        self.db_service.save_codes(&all_codes).await?;
+        // Sightings alone keep the cached responses and Last-Modified
+        if self.storage.save_codes(&all_codes).await? {
+            data_version::bump(Dataset::Codes);
+        }
+
+        for code in all_codes.iter().filter(|code| !existing_codes.contains_key(&code.code)) {
+            let entry = QueuedValidation::new(&code.code, ValidationPriority::New, seen_at);
+            if let Err(e) = self.storage.enqueue_validation(&entry).await {
+                error!("Failed to queue validation of new code {}: {}", code.code, e);
+            }
+        }
+        if !added.is_empty() {
+            validation_queue::wake();
+        }
+
+        // Announce new codes only once they can be read back from the API
+        let bus = event_bus::instance();
+        for summary in added {
+            bus.publish("code.added", summary);
+        }
         
-        Ok(self.db_service.get_codes().await?)
+        self.storage.get_codes().await
     }
 
-    pub async fn validate_active_codes(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let validator = ValidatorService::new();
-        let (active_codes, _) = self.db_service.get_codes().await?;
-        
+    /// Queues every active code that has not been validated for `validator.revalidate_after`
+    /// seconds. Returns the number of codes added to the queue.
+    pub async fn queue_revalidations(&self) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let (active_codes, _) = self.storage.get_codes().await?;
+        let now = DateTime::now();
+        let stale_before = now.timestamp_millis() - self.validator_config.revalidate_after as i64 * 1000;
+
+        let mut queued = 0;
         for code in active_codes {
-            match validator.validate_code(&code).await {
-                Ok(validation_result) => {
-                    match validation_result {
-                        ValidationResult::Valid | ValidationResult::AlreadyRedeemed => {
-                            debug!("Code {} is still valid", code.code);
-                        },
-                        ValidationResult::Expired | ValidationResult::Invalid | ValidationResult::MaxUsageReached => {
-                            info!("Code {} is now inactive", code.code);
-                            if let Err(e) = self.db_service.update_code_status(&code.code, false).await {
-                                error!("Failed to update code status: {}", e);
-                            }
-                        },
-                        ValidationResult::Cooldown => {
-                            warn!("Code {} is in cooldown, will check again later", code.code);
-                        },
+            if code.last_validated_at.is_some_and(|at| at.timestamp_millis() > stale_before)
+                || !self.validator.supports(code.server) {
+                continue;
+            }
+            let waiting_since = code.last_validated_at.or(code.first_seen_at).or(code.date).unwrap_or(now);
+            let entry = QueuedValidation::new(&code.code, ValidationPriority::Revalidation, waiting_since);
+            if self.storage.enqueue_validation(&entry).await? {
+                queued += 1;
+            }
+        }
+
+        if queued > 0 {
+            validation_queue::wake();
+        }
+        Ok(queued)
+    }
+
+    /// Validates the most urgent code in the validation queue that is ready in every region
+    /// of its server. The code stays active while it works in any region. Returns `false` if
+    /// no code is ready.
+    pub async fn validate_next(&self) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let queue = self.storage.get_validation_queue().await?;
+        metrics::instance().validation_queue_length.set(queue.len() as i64);
+        let Some(entry) = validation_queue::next_ready(&queue, DateTime::now()).cloned() else {
+            return Ok(false);
+        };
+
+        let code = match self.storage.get_code(&entry.code).await? {
+            Some(code) if code.active => code,
+            _ => {
+                debug!("Dropping {} from the validation queue, it is no longer active", entry.code);
+                self.storage.remove_queued_validation(&entry.code).await?;
+                return Ok(true);
+            }
+        };
+        if !self.validator.supports(code.server) {
+            warn!("No {} validator account, leaving code {} unvalidated", code.server.as_str(), code.code);
+            self.storage.remove_queued_validation(&code.code).await?;
+            return Ok(true);
+        }
+        let is_new = entry.priority == ValidationPriority::New;
+        let validations = self.validator.validate_code(&code).await;
+
+        // Conclusive results replace the status of their region, the others keep the last one
+        let mut code = code;
+        let checked_at = DateTime::now();
+        let mut conclusive = 0;
+        let mut works = false;
+        // Regions whose every account is quarantined, which cannot say anything about the code
+        let mut skipped = 0;
+        for validation in &validations {
+            match &validation.result {
+                Ok(result) => match result.works() {
+                    Some(valid) => {
+                        debug!("Code {} is {} in {}", code.code, if valid { "valid" } else { "not valid" }, validation.region);
+                        code.record_region(RegionStatus { region: validation.region.clone(), valid, checked_at });
+                        conclusive += 1;
+                        works |= valid;
+                    },
+                    None => match result {
                         ValidationResult::InvalidCredentials => {
-                            error!("Invalid account credentials, stopping validation");
-                            return Err("Invalid account credentials".into());
+                            error!("No usable {} validator account, skipping the region for code {}", validation.region, code.code);
+                            skipped += 1;
                         },
-                        ValidationResult::Unknown(code, message) => {
-                            error!("Unknown validation result: {} - {}", code, message);
-                        }
-                    }
+                        ValidationResult::Unknown(retcode, message) => {
+                            error!("Unknown validation result for code {} in {}: {} - {}", code.code, validation.region, retcode, message);
+                        },
+                        _ => warn!("Code {} is in cooldown in {}", code.code, validation.region),
+                    },
                 },
-                Err(e) => {
-                    error!("Failed to validate code {}: {}", code.code, e);
-                }
+                Err(e) => error!("Failed to validate code {} in {}: {}", code.code, validation.region, e),
             }
-            
-            tokio::time::sleep(tokio::time::Duration::from_secs(15)).await;
         }
-        
-        Ok(())
+        if conclusive > 0 {
+            self.storage.update_code_regions(&code.code, &code.regions).await?;
+        }
+
+        if works {
+            // Regions that were inconclusive are checked again with the next revalidation
+            debug!("Code {} is valid", code.code);
+            self.storage.mark_validated(&code.code).await?;
+            self.storage.remove_queued_validation(&code.code).await?;
+            data_version::bump(Dataset::Codes);
+            if is_new {
+                self.notifier.notify(&[CodeEvent::Validated], &code);
+            }
+        } else if conclusive == validations.len() {
+            info!("Code {} is now inactive", code.code);
+            self.storage.mark_validated(&code.code).await?;
+            self.storage.update_code_status(&code.code, false).await?;
+            self.storage.remove_queued_validation(&code.code).await?;
+            data_version::bump(Dataset::Codes);
+            let deactivated = RedemptionCode { active: false, ..code };
+            self.notifier.notify(&[CodeEvent::Deactivated], &deactivated);
+            event_bus::instance().publish("code.deactivated", code_summary(&deactivated));
+        } else {
+            if conclusive > 0 {
+                data_version::bump(Dataset::Codes);
+            }
+            // A region that did not answer may be the one the code works in, so it is kept
+            self.retry_later(&entry).await?;
+            if skipped == validations.len() {
+                // Left queued for when the accounts are fixed
+                error!("Invalid account credentials, pausing validation");
+                return Err("Invalid account credentials".into());
+            }
+            warn!("Code {} could not be validated in every region, will check again later", code.code);
+        }
+
+        Ok(true)
+    }
+
+    async fn retry_later(&self, entry: &QueuedValidation) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let retry = entry.retry(&self.validator_config, DateTime::now());
+        debug!("Retrying {} after {}", retry.code, retry.not_before);
+        self.storage.update_queued_validation(&retry).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolvers::Server;
+    use crate::config::{HoyolabConfig, UpstreamConfig};
+    use crate::services::storage::memory::MemoryStorage;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use wiremock::matchers::{method, path, query_param};
+
+    const PAGES: &[(&str, &str)] = &[
+        ("/game8", include_str!("../resolvers/fixtures/game8.html")),
+        ("/prydwen", include_str!("../resolvers/fixtures/prydwen.html")),
+        ("/fandom", include_str!("../resolvers/fixtures/fandom.html")),
+        ("/polygon", include_str!("../resolvers/fixtures/polygon.html")),
+        ("/eurogamer", include_str!("../resolvers/fixtures/eurogamer.html")),
+        ("/hoyolab", include_str!("../resolvers/fixtures/hoyolab.json")),
+    ];
+
+    fn stub_settings(uri: &str) -> Settings {
+        Settings {
+            hoyolab: HoyolabConfig {
+                region: "prod_official_usa".to_string(),
+                uid: "800000000".to_string(),
+                cookie: "ltoken_v2=stub".to_string(),
+                user_agent: "starrail-api-tests".to_string(),
+                server: Server::Global,
+            },
+            upstream: UpstreamConfig {
+                game8: format!("{}/game8", uri),
+                prydwen: format!("{}/prydwen", uri),
+                fandom: format!("{}/fandom", uri),
+                polygon: format!("{}/polygon", uri),
+                eurogamer: format!("{}/eurogamer", uri),
+                hoyolab: format!("{}/hoyolab", uri),
+                redeem: format!("{}/redeem", uri),
+                redeem_cn: format!("{}/redeem_cn", uri),
+                news: uri.to_string(),
+            },
+            validator: ValidatorConfig {
+                accounts: vec![HoyolabConfig {
+                    region: "prod_gf_cn".to_string(),
+                    uid: "100000000".to_string(),
+                    cookie: "cookie_token=stub".to_string(),
+                    user_agent: String::new(),
+                    server: Server::Cn,
+                }],
+                requests_per_minute: 0,
+                revalidate_after: 0,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    async fn mount_cn_redeem(server: &MockServer) {
+        Mock::given(method("GET"))
+            .and(path("/redeem_cn"))
+            .and(query_param("game_biz", "hkrpg_cn"))
+            .and(query_param("uid", "100000000"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "retcode": 0,
+                "message": "OK",
+            })))
+            .mount(server)
+            .await;
+    }
+
+    fn stored_code(code: &str) -> RedemptionCode {
+        RedemptionCode {
+            id: None,
+            code: code.to_string(),
+            rewards: vec!["50 Stellar Jade".to_string()],
+            items: Vec::new(),
+            source: "Game8".to_string(),
+            sources: Vec::new(),
+            active: true,
+            date: None,
+            first_seen_at: None,
+            last_seen_in_source_at: None,
+            last_validated_at: None,
+            deactivated_at: None,
+            server: Server::Global,
+            regions: Vec::new(),
+        }
+    }
+
+    async fn drain_queue(service: &CodeService) {
+        while service.validate_next().await.unwrap() {}
+    }
+
+    async fn mount_redeem(server: &MockServer, code: &str, retcode: i32) {
+        Mock::given(method("GET"))
+            .and(path("/redeem"))
+            .and(query_param("cdkey", code))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "retcode": retcode,
+                "message": "stub",
+            })))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_scrape_validate_and_store_against_stub_server() {
+        let server = MockServer::start().await;
+        for (page, body) in PAGES {
+            Mock::given(method("GET"))
+                .and(path(*page))
+                .respond_with(ResponseTemplate::new(200).set_body_string(*body))
+                .mount(&server)
+                .await;
+        }
+        mount_redeem(&server, "HSRGRANDOPEN1", -2001).await;
+        Mock::given(method("GET"))
+            .and(path("/redeem"))
+            .and(query_param("uid", "800000000"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "retcode": 0,
+                "message": "OK",
+            })))
+            .with_priority(10)
+            .mount(&server)
+            .await;
+        mount_cn_redeem(&server).await;
+
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::new());
+        let service = CodeService::with_storage(stub_settings(&server.uri()), storage.clone(), Arc::default());
+
+        // New codes stay active until the queue validates them
+        let (active, _) = service.get_all_codes().await.unwrap();
+        assert_eq!(active.len(), 7);
+        assert_eq!(storage.get_validation_queue().await.unwrap().len(), 7);
+        drain_queue(&service).await;
+
+        let (active, inactive) = storage.get_codes().await.unwrap();
+        let mut active_codes: Vec<&str> = active.iter().map(|code| code.code.as_str()).collect();
+        active_codes.sort();
+        assert_eq!(active_codes, vec!["CNSERVERCODE88", "HSR2024ABCDE", "LIVESTREAM3X9K", "LIVESTREAM7Q2M", "STARRAILGIFT", "VAJEGY4MNMDK"]);
+        assert_eq!(inactive.len(), 1);
+        assert_eq!(inactive[0].code, "HSRGRANDOPEN1");
+        assert!(inactive[0].deactivated_at.is_some());
+
+        // Reported by every source, with the structured items from Hoyolab
+        let gift = storage.get_code("STARRAILGIFT").await.unwrap().unwrap();
+        assert_eq!(gift.sources.len(), 6);
+        assert!(gift.last_validated_at.is_some());
+
+        // China-only codes are validated against the CN endpoint with the CN account
+        let cn_code = storage.get_code("CNSERVERCODE88").await.unwrap().unwrap();
+        assert_eq!(cn_code.server, Server::Cn);
+        assert!(cn_code.last_validated_at.is_some());
+
+        // A later validation run deactivates codes the redeem endpoint now rejects
+        server.reset().await;
+        mount_redeem(&server, "LIVESTREAM3X9K", -2001).await;
+        mount_redeem(&server, "LIVESTREAM7Q2M", -2001).await;
+        mount_cn_redeem(&server).await;
+        Mock::given(method("GET"))
+            .and(path("/redeem"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "retcode": -2017,
+                "message": "Redemption code already in use",
+            })))
+            .with_priority(10)
+            .mount(&server)
+            .await;
+
+        assert_eq!(service.queue_revalidations().await.unwrap(), 6);
+        drain_queue(&service).await;
+        let (active, inactive) = storage.get_codes().await.unwrap();
+        assert_eq!(active.len(), 4);
+        assert_eq!(inactive.len(), 3);
+        assert!(storage.get_validation_queue().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_is_retried_later() {
+        let server = MockServer::start().await;
+        mount_redeem(&server, "STARRAILGIFT", -2016).await;
+
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::new());
+        let service = CodeService::with_storage(stub_settings(&server.uri()), storage.clone(), Arc::default());
+        storage.save_codes(&[stored_code("STARRAILGIFT")]).await.unwrap();
+        storage.enqueue_validation(&QueuedValidation::new("STARRAILGIFT", ValidationPriority::New, DateTime::now())).await.unwrap();
+
+        assert!(service.validate_next().await.unwrap());
+        let queue = storage.get_validation_queue().await.unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].retries, 1);
+        assert!(queue[0].not_before > DateTime::now());
+        // Nothing else is ready until the retry delay has passed
+        assert!(!service.validate_next().await.unwrap());
+        assert!(storage.get_code("STARRAILGIFT").await.unwrap().unwrap().active);
     }
-} 
\ No newline at end of file
+
+    async fn mount_regional_redeem(server: &MockServer, code: &str, region: &str, retcode: i32) {
+        Mock::given(method("GET"))
+            .and(path("/redeem"))
+            .and(query_param("cdkey", code))
+            .and(query_param("region", region))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "retcode": retcode,
+                "message": "stub",
+            })))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_region_locked_code_stays_active() {
+        let server = MockServer::start().await;
+        mount_regional_redeem(&server, "USAONLYCODE1", "prod_official_usa", 0).await;
+        mount_regional_redeem(&server, "USAONLYCODE1", "prod_official_asia", -2003).await;
+
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::new());
+        let mut settings = stub_settings(&server.uri());
+        settings.validator.accounts.push(HoyolabConfig {
+            region: "prod_official_asia".to_string(),
+            uid: "800000001".to_string(),
+            cookie: "ltoken_v2=stub".to_string(),
+            user_agent: String::new(),
+            server: Server::Global,
+        });
+        let service = CodeService::with_storage(settings, storage.clone(), Arc::default());
+        storage.save_codes(&[stored_code("USAONLYCODE1")]).await.unwrap();
+        storage.enqueue_validation(&QueuedValidation::new("USAONLYCODE1", ValidationPriority::New, DateTime::now())).await.unwrap();
+
+        drain_queue(&service).await;
+        let code = storage.get_code("USAONLYCODE1").await.unwrap().unwrap();
+        assert!(code.active);
+        let regions: Vec<(&str, bool)> = code.regions.iter().map(|status| (status.region.as_str(), status.valid)).collect();
+        assert_eq!(regions, vec![("prod_official_usa", true), ("prod_official_asia", false)]);
+
+        // Once it stops working in every region it is deactivated
+        server.reset().await;
+        mount_regional_redeem(&server, "USAONLYCODE1", "prod_official_usa", -2001).await;
+        mount_regional_redeem(&server, "USAONLYCODE1", "prod_official_asia", -2003).await;
+        assert_eq!(service.queue_revalidations().await.unwrap(), 1);
+        drain_queue(&service).await;
+        let code = storage.get_code("USAONLYCODE1").await.unwrap().unwrap();
+        assert!(!code.active);
+        assert!(code.regions.iter().all(|status| !status.valid));
+    }
+
+    #[tokio::test]
+    async fn test_quarantined_region_is_skipped() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/redeem"))
+            .and(query_param("uid", "800000000"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "retcode": -1071,
+                "message": "stub",
+            })))
+            .mount(&server)
+            .await;
+        mount_regional_redeem(&server, "EXPIREDCODE1", "prod_official_asia", -2001).await;
+
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::new());
+        let pool: Arc<AccountPool> = Arc::default();
+        let mut settings = stub_settings(&server.uri());
+        settings.validator.accounts.push(HoyolabConfig {
+            region: "prod_official_asia".to_string(),
+            uid: "800000001".to_string(),
+            cookie: "ltoken_v2=stub".to_string(),
+            user_agent: String::new(),
+            server: Server::Global,
+        });
+        let service = CodeService::with_storage(settings, storage.clone(), pool.clone());
+        storage.save_codes(&[stored_code("EXPIREDCODE1"), stored_code("STARRAILGIFT")]).await.unwrap();
+        storage.enqueue_validation(&QueuedValidation::new("EXPIREDCODE1", ValidationPriority::New, DateTime::now())).await.unwrap();
+
+        // Rejected in Asia, but it may still work in America, so it stays active and queued
+        assert!(service.validate_next().await.unwrap());
+        let code = storage.get_code("EXPIREDCODE1").await.unwrap().unwrap();
+        assert!(code.active);
+        let regions: Vec<(&str, bool)> = code.regions.iter().map(|status| (status.region.as_str(), status.valid)).collect();
+        assert_eq!(regions, vec![("prod_official_asia", false)]);
+        let queue = storage.get_validation_queue().await.unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].code, "EXPIREDCODE1");
+        assert_eq!(queue[0].retries, 1);
+        storage.remove_queued_validation("EXPIREDCODE1").await.unwrap();
+
+        // Without any usable account validation pauses, and the code waits for its retry
+        let service = CodeService::with_storage(stub_settings(&server.uri()), storage.clone(), pool);
+        storage.enqueue_validation(&QueuedValidation::new("STARRAILGIFT", ValidationPriority::New, DateTime::now())).await.unwrap();
+        assert!(service.validate_next().await.is_err());
+        let queue = storage.get_validation_queue().await.unwrap();
+        assert_eq!(queue[0].retries, 1);
+        assert!(!service.validate_next().await.unwrap());
+    }
+}
@@ -0,0 +1,73 @@
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::LazyLock;
+
+static CODES: LazyLock<DataVersion> = LazyLock::new(DataVersion::new);
+static NEWS: LazyLock<DataVersion> = LazyLock::new(DataVersion::new);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dataset {
+    Codes,
+    News,
+}
+
+/// A snapshot of a dataset's version. `modified_at` is in Unix seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Version {
+    pub number: u64,
+    pub modified_at: i64,
+}
+
+/// Counts writes to a dataset so handlers can tell whether a cached response is still current.
+/// Versions are per process and start over on restart, with `modified_at` set to the start time.
+struct DataVersion {
+    number: AtomicU64,
+    modified_at: AtomicI64,
+}
+
+impl DataVersion {
+    fn new() -> Self {
+        Self {
+            number: AtomicU64::new(0),
+            modified_at: AtomicI64::new(chrono::Utc::now().timestamp()),
+        }
+    }
+}
+
+fn dataset(dataset: Dataset) -> &'static DataVersion {
+    match dataset {
+        Dataset::Codes => &CODES,
+        Dataset::News => &NEWS,
+    }
+}
+
+/// Marks `dataset` as changed. Call after every write to storage.
+pub fn bump(which: Dataset) {
+    let version = dataset(which);
+    version.modified_at.store(chrono::Utc::now().timestamp(), Ordering::SeqCst);
+    version.number.fetch_add(1, Ordering::SeqCst);
+}
+
+pub fn current(which: Dataset) -> Version {
+    let version = dataset(which);
+    // Read the counter first so a concurrent bump can only make modified_at newer
+    let number = version.number.load(Ordering::SeqCst);
+    Version {
+        number,
+        modified_at: version.modified_at.load(Ordering::SeqCst),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bump() {
+        let before = current(Dataset::Codes);
+        bump(Dataset::Codes);
+
+        let after = current(Dataset::Codes);
+        assert!(after.number > before.number);
+        assert!(after.modified_at >= before.modified_at);
+    }
+}
@@ -1,17 +1,30 @@
 use mongodb::{
-    Client, Database,
+    Client, Collection, Database,
     options::{ClientOptions, ServerApi, ServerApiVersion, UpdateOptions},
-    bson::{doc, DateTime},
+    bson::{doc, to_bson, DateTime, Document},
+    event::EventHandler,
 };
+use async_trait::async_trait;
 use std::sync::Arc;
 use tokio::sync::OnceCell;
 use log::{info, debug, error};
 use futures::TryStreamExt;
-use crate::resolvers::RedemptionCode;
+use crate::resolvers::{RedemptionCode, RegionStatus};
+use crate::resolvers::news::NewsItem;
 use crate::config::Settings;
+use super::metrics;
+use super::news_service::EventStatus;
+use super::notification_service::WebhookDelivery;
+use super::storage::{CodeRepository, DeliveryLogRepository, HealthCheck, NewsRepository, NewsQuery, SavedNews, StorageResult, ValidationLogRepository, ValidationQueueRepository, merge_scraped_code, new_stored_code};
+use super::validation_queue::QueuedValidation;
+use super::validator_service::ValidationAttempt;
 
 static DB_INSTANCE: OnceCell<Arc<DbService>> = OnceCell::const_new();
 
+/// Number of data migrations in `DbService::migrate`.
+const MIGRATIONS: i32 = 1;
+
+/// MongoDB storage backend.
 pub struct DbService {
     db: Database,
 }
@@ -50,17 +63,73 @@ impl DbService {
             mongodb::options::Compressor::Zstd { level: Some(3) },
         ]);
 
+        client_options.cmap_event_handler = Some(EventHandler::callback(|event| {
+            metrics::instance().observe_cmap_event(event);
+        }));
+
         let client = Client::with_options(client_options)?;
         let db = client.database(&config.mongodb.database);
         
         db.run_command(doc! {"ping": 1}).await?;
+
+        Self::migrate(&db).await?;
         
         info!("Connected to MongoDB successfully");
         Ok(Self { db })
     }
 
-    pub async fn save_codes(&self, codes: &[RedemptionCode]) -> Result<(), mongodb::error::Error> {
-        let collection = self.db.collection::<RedemptionCode>("codes");
+    /// Applies the data migrations the database has not seen yet, recording the number
+    /// applied in the `schema` collection like `user_version` in the SQLite backend.
+    async fn migrate(db: &Database) -> Result<(), mongodb::error::Error> {
+        let schema = db.collection::<Document>("schema");
+        let applied = schema.find_one(doc! { "_id": "version" }).await?
+            .and_then(|version| version.get_i32("version").ok())
+            .unwrap_or(0);
+
+        for version in applied..MIGRATIONS {
+            match version {
+                // Deliveries logged before webhook URLs were redacted kept them in full, secrets included
+                0 => {
+                    db.collection::<Document>("webhook_deliveries")
+                        .update_many(doc! { "url": { "$exists": true } }, doc! { "$unset": { "url": "" }, "$set": { "webhook": "redacted" } })
+                        .await?;
+                },
+                _ => unreachable!("no migration {}", version),
+            }
+            schema.update_one(doc! { "_id": "version" }, doc! { "$set": { "version": version + 1 } })
+                .upsert(true)
+                .await?;
+            info!("Applied MongoDB migration {}", version + 1);
+        }
+        Ok(())
+    }
+
+    fn codes(&self) -> Collection<RedemptionCode> {
+        self.db.collection("codes")
+    }
+
+    fn news(&self) -> Collection<NewsItem> {
+        self.db.collection("news")
+    }
+
+    fn deliveries(&self) -> Collection<WebhookDelivery> {
+        self.db.collection("webhook_deliveries")
+    }
+
+    fn validations(&self) -> Collection<ValidationAttempt> {
+        self.db.collection("validations")
+    }
+
+    fn validation_queue(&self) -> Collection<QueuedValidation> {
+        self.db.collection("validation_queue")
+    }
+}
+
+#[async_trait]
+impl CodeRepository for DbService {
+    async fn save_codes(&self, codes: &[RedemptionCode]) -> StorageResult<bool> {
+        let collection = self.codes();
+        let mut changed = false;
         
         for code in codes {
             let now = DateTime::now();
@@ -70,22 +139,41 @@ impl DbService {
                 .await?;
             
             let update = match existing {
-                Some(_) => {
-                    doc! {
-                        "$set": {
-                            "rewards": &code.rewards,
-                            "source": &code.source,
-                        }
+                Some(mut existing) => {
+                    let backfill_first_seen = existing.first_seen_at.is_none();
+                    changed |= merge_scraped_code(&mut existing, code, now);
+
+                    let mut set = doc! {
+                        "rewards": &existing.rewards,
+                        "items": to_bson(&existing.items)?,
+                        "source": &existing.source,
+                        "sources": to_bson(&existing.sources)?,
+                        "server": existing.server.as_str(),
+                        "last_seen_in_source_at": existing.last_seen_in_source_at,
+                    };
+                    if backfill_first_seen {
+                        set.insert("first_seen_at", existing.first_seen_at);
                     }
+
+                    doc! { "$set": set }
                 },
                 None => {
+                    changed = true;
+                    let stored = new_stored_code(code, now);
                     doc! {
                         "$set": {
-                            "code": &code.code,
-                            "rewards": &code.rewards,
-                            "source": &code.source,
-                            "date": now,
-                            "active": code.active,
+                            "code": &stored.code,
+                            "rewards": &stored.rewards,
+                            "items": to_bson(&stored.items)?,
+                            "source": &stored.source,
+                            "sources": to_bson(&stored.sources)?,
+                            "date": stored.date,
+                            "active": stored.active,
+                            "first_seen_at": stored.first_seen_at,
+                            "last_seen_in_source_at": stored.last_seen_in_source_at,
+                            "last_validated_at": stored.last_validated_at,
+                            "deactivated_at": stored.deactivated_at,
+                            "server": stored.server.as_str(),
                         }
                     }
                 }
@@ -101,11 +189,15 @@ impl DbService {
                 .await?;
         }
         debug!("Saved {} codes to database", codes.len());
-        Ok(())
+        Ok(changed)
+    }
+
+    async fn get_code(&self, code: &str) -> StorageResult<Option<RedemptionCode>> {
+        Ok(self.codes().find_one(doc! { "code": code }).await?)
     }
 
-    pub async fn get_codes(&self) -> Result<(Vec<RedemptionCode>, Vec<RedemptionCode>), mongodb::error::Error> {
-        let collection = self.db.collection::<RedemptionCode>("codes");
+    async fn get_codes(&self) -> StorageResult<(Vec<RedemptionCode>, Vec<RedemptionCode>)> {
+        let collection = self.codes();
         let mut active = Vec::new();
         let mut inactive = Vec::new();
 
@@ -124,11 +216,13 @@ impl DbService {
         Ok((active, inactive))
     }
 
-    pub async fn update_code_status(&self, code: &str, active: bool) -> Result<(), mongodb::error::Error> {
-        let collection = self.db.collection::<RedemptionCode>("codes");
+    async fn update_code_status(&self, code: &str, active: bool) -> StorageResult<()> {
+        let collection = self.codes();
+        let deactivated_at = if active { None } else { Some(DateTime::now()) };
         let update = doc! {
             "$set": {
-                "active": active
+                "active": active,
+                "deactivated_at": deactivated_at,
             }
         };
 
@@ -140,7 +234,184 @@ impl DbService {
         Ok(())
     }
 
-    pub fn get_database(&self) -> Database {
-        self.db.clone()
+    async fn mark_validated(&self, code: &str) -> StorageResult<()> {
+        self.codes()
+            .update_one(doc! { "code": code }, doc! { "$set": { "last_validated_at": DateTime::now() } })
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update_code_regions(&self, code: &str, regions: &[RegionStatus]) -> StorageResult<()> {
+        self.codes()
+            .update_one(doc! { "code": code }, doc! { "$set": { "regions": to_bson(regions)? } })
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NewsRepository for DbService {
+    async fn save_news(&self, news: &[NewsItem]) -> StorageResult<SavedNews> {
+        debug!("Starting to save news items to database");
+        let collection = self.news();
+        let mut saved = SavedNews::default();
+
+        for item in news {
+            let filter = doc! {
+                "id": &item.external_id,
+                "lang": &item.lang
+            };
+
+            let update = doc! {
+                "$set": {
+                    "id": &item.external_id,
+                    "title": &item.title,
+                    "description": &item.description,
+                    "createdAt": &item.created_at,
+                    "banner": &item.banner,
+                    "url": &item.url,
+                    "type": &item.news_type,
+                    "lang": &item.lang,
+                    "startAt": item.start_at,
+                    "endAt": item.end_at,
+                }
+            };
+
+            let update_result = collection
+                .update_one(filter, update)
+                .upsert(true)
+                .await?;
+
+            // `$set` with the stored values leaves the document unmodified
+            if update_result.modified_count > 0 {
+                saved.changed = true;
+            }
+            if update_result.upserted_id.is_some() {
+                info!("New news item added: {} ({})", item.title, item.lang);
+                saved.added.push(item.clone());
+                saved.changed = true;
+            }
+        }
+
+        debug!("Successfully saved news items to database");
+        Ok(saved)
     }
-} 
\ No newline at end of file
+
+    async fn get_news(&self, query: &NewsQuery<'_>) -> StorageResult<Vec<NewsItem>> {
+        let mut filter = Document::new();
+        
+        if let Some(type_str) = query.news_type {
+            filter.insert("type", type_str);
+        }
+
+        if let Some(status) = query.status {
+            let now = chrono::Utc::now().timestamp();
+            match status {
+                EventStatus::Ongoing => {
+                    filter.insert("startAt", doc! { "$lte": now });
+                    filter.insert("endAt", doc! { "$gt": now });
+                },
+                EventStatus::Upcoming => {
+                    filter.insert("startAt", doc! { "$gt": now });
+                },
+                EventStatus::Ended => {
+                    filter.insert("endAt", doc! { "$lte": now });
+                },
+            }
+        }
+
+        filter.insert("lang", query.lang);
+
+        debug!("Applying MongoDB filter: {:?}", filter);
+
+        let mut cursor = self.news()
+            .find(filter)
+            .sort(doc! { "createdAt": -1 })
+            .limit(query.limit as i64)
+            .await?;
+
+        let mut news = Vec::new();
+        
+        while let Some(item) = cursor.try_next().await? {
+            news.push(item);
+        }
+
+        Ok(news)
+    }
+}
+
+#[async_trait]
+impl DeliveryLogRepository for DbService {
+    async fn log_delivery(&self, delivery: &WebhookDelivery) -> StorageResult<()> {
+        self.deliveries().insert_one(delivery).await?;
+        Ok(())
+    }
+
+    async fn get_deliveries(&self, limit: usize) -> StorageResult<Vec<WebhookDelivery>> {
+        let cursor = self.deliveries()
+            .find(doc! {})
+            .sort(doc! { "delivered_at": -1 })
+            .limit(limit as i64)
+            .await?;
+
+        Ok(cursor.try_collect().await?)
+    }
+}
+
+#[async_trait]
+impl ValidationLogRepository for DbService {
+    async fn log_validation(&self, attempt: &ValidationAttempt) -> StorageResult<()> {
+        self.validations().insert_one(attempt).await?;
+        Ok(())
+    }
+
+    async fn get_validations(&self, code: &str, limit: usize) -> StorageResult<Vec<ValidationAttempt>> {
+        let cursor = self.validations()
+            .find(doc! { "code": code })
+            .sort(doc! { "validated_at": -1 })
+            .limit(limit as i64)
+            .await?;
+
+        Ok(cursor.try_collect().await?)
+    }
+}
+
+#[async_trait]
+impl ValidationQueueRepository for DbService {
+    async fn enqueue_validation(&self, entry: &QueuedValidation) -> StorageResult<bool> {
+        let result = self.validation_queue()
+            .update_one(doc! { "code": &entry.code }, doc! { "$setOnInsert": to_bson(entry)? })
+            .upsert(true)
+            .await?;
+
+        Ok(result.upserted_id.is_some())
+    }
+
+    async fn update_queued_validation(&self, entry: &QueuedValidation) -> StorageResult<()> {
+        self.validation_queue()
+            .replace_one(doc! { "code": &entry.code }, entry)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_queued_validation(&self, code: &str) -> StorageResult<()> {
+        self.validation_queue().delete_one(doc! { "code": code }).await?;
+        Ok(())
+    }
+
+    async fn get_validation_queue(&self) -> StorageResult<Vec<QueuedValidation>> {
+        let cursor = self.validation_queue().find(doc! {}).await?;
+        Ok(cursor.try_collect().await?)
+    }
+}
+
+#[async_trait]
+impl HealthCheck for DbService {
+    async fn ping(&self) -> StorageResult<()> {
+        self.db.run_command(doc! {"ping": 1}).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{LazyLock, Mutex};
+use crate::resolvers::RedemptionCode;
+use crate::rewards;
+
+/// Scrapes kept per resolver as the baseline for the count and reward checks.
+const HISTORY: usize = 30;
+/// Scrapes needed before a drop in count is judged against the baseline.
+const MIN_HISTORY: usize = 5;
+/// A scrape finding less than this share of the usual count is a drop.
+const COUNT_DROP_RATIO: f64 = 0.5;
+/// Sources that usually list fewer codes than this are too small to judge drops on.
+const MIN_AVERAGE_COUNT: f64 = 3.0;
+/// Share of codes with unparseable rewards that counts as broken parsing.
+const MISSING_AMOUNTS_RATIO: f64 = 0.5;
+
+static CODE_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[A-Z0-9]{6,20}$").unwrap());
+
+static DRIFT_DETECTOR: LazyLock<DriftDetector> = LazyLock::new(DriftDetector::default);
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Anomaly {
+    /// Codes that do not look like redemption codes, e.g. a selector now matching headings.
+    InvalidCodes { codes: Vec<String> },
+    /// Codes with no rewards or rewards without an amount, while the source normally has them.
+    MissingAmounts { affected: usize, total: usize },
+    /// Far fewer codes than the source usually lists.
+    CountDrop { count: usize, average: f64 },
+}
+
+impl Anomaly {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Anomaly::InvalidCodes { .. } => "invalid_codes",
+            Anomaly::MissingAmounts { .. } => "missing_amounts",
+            Anomaly::CountDrop { .. } => "count_drop",
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Anomaly::InvalidCodes { codes } => format!("{} codes do not look like redemption codes: {}", codes.len(), codes.join(", ")),
+            Anomaly::MissingAmounts { affected, total } => format!("{} of {} codes have rewards without amounts", affected, total),
+            Anomaly::CountDrop { count, average } => format!("found {} codes, usually {:.1}", count, average),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DriftReport {
+    pub source: String,
+    pub anomalies: Vec<Anomaly>,
+}
+
+#[derive(Clone, Copy)]
+struct Sample {
+    count: usize,
+    missing_share: f64,
+}
+
+#[derive(Default)]
+struct SourceHistory {
+    samples: VecDeque<Sample>,
+    /// Kinds found by the previous check, so a persisting anomaly is only reported once.
+    active: Vec<&'static str>,
+}
+
+/// Compares each successful scrape with the resolver's recent output to catch page changes
+/// that break parsing without causing an error.
+#[derive(Default)]
+pub struct DriftDetector {
+    sources: Mutex<HashMap<String, SourceHistory>>,
+}
+
+pub fn instance() -> &'static DriftDetector {
+    &DRIFT_DETECTOR
+}
+
+impl DriftDetector {
+    /// Checks a scrape of `source` and records it in the history. Returns a report only when
+    /// an anomaly appears that the previous scrape did not already have.
+    pub fn check(&self, source: &str, codes: &[RedemptionCode]) -> Option<DriftReport> {
+        let mut sources = self.sources.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let history = sources.entry(source.to_string()).or_default();
+        let mut anomalies = Vec::new();
+
+        let invalid: Vec<String> = codes.iter()
+            .filter(|code| !CODE_PATTERN.is_match(&code.code))
+            .map(|code| code.code.clone())
+            .collect();
+        if !invalid.is_empty() {
+            anomalies.push(Anomaly::InvalidCodes { codes: invalid });
+        }
+
+        let missing = codes.iter()
+            .filter(|code| {
+                let items = if code.items.is_empty() { rewards::parse_rewards(&code.rewards) } else { code.items.clone() };
+                items.is_empty() || items.iter().any(|item| item.amount.is_none())
+            })
+            .count();
+        let missing_share = if codes.is_empty() { 0.0 } else { missing as f64 / codes.len() as f64 };
+        let baseline_missing = average(&history.samples, |sample| sample.missing_share);
+        if missing_share >= MISSING_AMOUNTS_RATIO && baseline_missing.is_none_or(|baseline| baseline < MISSING_AMOUNTS_RATIO / 2.0) {
+            anomalies.push(Anomaly::MissingAmounts { affected: missing, total: codes.len() });
+        }
+
+        if history.samples.len() >= MIN_HISTORY {
+            let average_count = average(&history.samples, |sample| sample.count as f64).unwrap_or_default();
+            if average_count >= MIN_AVERAGE_COUNT && (codes.len() as f64) < average_count * COUNT_DROP_RATIO {
+                anomalies.push(Anomaly::CountDrop { count: codes.len(), average: average_count });
+            }
+        }
+
+        if history.samples.len() == HISTORY {
+            history.samples.pop_front();
+        }
+        history.samples.push_back(Sample { count: codes.len(), missing_share });
+
+        let previous = std::mem::replace(&mut history.active, anomalies.iter().map(Anomaly::kind).collect());
+        if anomalies.iter().all(|anomaly| previous.contains(&anomaly.kind())) {
+            return None;
+        }
+
+        Some(DriftReport { source: source.to_string(), anomalies })
+    }
+}
+
+fn average(samples: &VecDeque<Sample>, value: impl Fn(&Sample) -> f64) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    Some(samples.iter().map(value).sum::<f64>() / samples.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolvers::Server;
+
+    fn code(code: &str, rewards: &[&str]) -> RedemptionCode {
+        RedemptionCode {
+            id: None,
+            code: code.to_string(),
+            rewards: rewards.iter().map(|reward| reward.to_string()).collect(),
+            items: Vec::new(),
+            source: "Eurogamer".to_string(),
+            sources: Vec::new(),
+            active: true,
+            date: None,
+            first_seen_at: None,
+            last_seen_in_source_at: None,
+            last_validated_at: None,
+            deactivated_at: None,
+            server: Server::Global,
+            regions: Vec::new(),
+        }
+    }
+
+    fn usual_codes() -> Vec<RedemptionCode> {
+        vec![
+            code("STARRAILGIFT", &["50 Stellar Jade", "10000 Credit"]),
+            code("VAJEGY4MNMDK", &["60 Stellar Jade"]),
+            code("HSRVER36TTSE", &["Stellar Jade x100"]),
+            code("5SAAUNX6BYVP", &["Credit x20000"]),
+        ]
+    }
+
+    #[test]
+    fn test_usual_output_is_quiet() {
+        let detector = DriftDetector::default();
+        for _ in 0..10 {
+            assert!(detector.check("Eurogamer", &usual_codes()).is_none());
+        }
+    }
+
+    #[test]
+    fn test_broken_selector() {
+        let detector = DriftDetector::default();
+        for _ in 0..MIN_HISTORY {
+            detector.check("Eurogamer", &usual_codes());
+        }
+
+        // The selector now matches a navigation list
+        let report = detector.check("Eurogamer", &[code("Latest news", &["Read more"])]).unwrap();
+        let kinds: Vec<&str> = report.anomalies.iter().map(Anomaly::kind).collect();
+        assert_eq!(kinds, vec!["invalid_codes", "missing_amounts", "count_drop"]);
+
+        // Reported once while it persists, and again after recovering and breaking anew
+        assert!(detector.check("Eurogamer", &[code("Latest news", &["Read more"])]).is_none());
+        assert!(detector.check("Eurogamer", &usual_codes()).is_none());
+        assert!(detector.check("Eurogamer", &[code("Latest news", &["Read more"])]).is_some());
+    }
+}
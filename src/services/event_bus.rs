@@ -0,0 +1,106 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{LazyLock, Mutex};
+use tokio::sync::broadcast;
+
+/// Events kept for clients resuming with `Last-Event-ID`.
+const REPLAY_CAPACITY: usize = 512;
+const CHANNEL_CAPACITY: usize = 256;
+
+static EVENT_BUS: LazyLock<EventBus> = LazyLock::new(EventBus::new);
+
+#[derive(Debug, Serialize, Clone)]
+pub struct StreamEvent {
+    pub id: u64,
+    pub event: String,
+    pub data: serde_json::Value,
+}
+
+struct Replay {
+    next_id: u64,
+    events: VecDeque<StreamEvent>,
+}
+
+/// In-process fan-out of live updates to the SSE and WebSocket streams.
+pub struct EventBus {
+    replay: Mutex<Replay>,
+    sender: broadcast::Sender<StreamEvent>,
+}
+
+pub fn instance() -> &'static EventBus {
+    &EVENT_BUS
+}
+
+impl EventBus {
+    fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            replay: Mutex::new(Replay { next_id: 1, events: VecDeque::with_capacity(REPLAY_CAPACITY) }),
+            sender,
+        }
+    }
+
+    pub fn publish(&self, event: &str, data: serde_json::Value) -> u64 {
+        let mut replay = self.replay.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let event = StreamEvent { id: replay.next_id, event: event.to_string(), data };
+        replay.next_id += 1;
+
+        if replay.events.len() == REPLAY_CAPACITY {
+            replay.events.pop_front();
+        }
+        replay.events.push_back(event.clone());
+
+        // Sending while holding the lock keeps replay and live events in order for new subscribers
+        let _ = self.sender.send(event.clone());
+        event.id
+    }
+
+    /// Returns the events published after `last_event_id` that are still buffered,
+    /// and a receiver for everything published from now on.
+    pub fn subscribe(&self, last_event_id: Option<u64>) -> (Vec<StreamEvent>, broadcast::Receiver<StreamEvent>) {
+        let replay = self.replay.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let backlog = match last_event_id {
+            Some(last_id) => replay.events.iter().filter(|event| event.id > last_id).cloned().collect(),
+            None => Vec::new(),
+        };
+
+        (backlog, self.sender.subscribe())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_from_last_event_id() {
+        let bus = EventBus::new();
+        let first = bus.publish("code.added", serde_json::json!({ "code": "STARRAILGIFT" }));
+        bus.publish("code.added", serde_json::json!({ "code": "VAJEGY4MNMDK" }));
+
+        let (backlog, mut receiver) = bus.subscribe(Some(first));
+        assert_eq!(backlog.len(), 1);
+        assert_eq!(backlog[0].data["code"], "VAJEGY4MNMDK");
+
+        let third = bus.publish("code.deactivated", serde_json::json!({ "code": "STARRAILGIFT" }));
+        let live = receiver.try_recv().unwrap();
+        assert_eq!(live.id, third);
+        assert_eq!(live.event, "code.deactivated");
+
+        // Fresh clients only get live events
+        let (backlog, _) = bus.subscribe(None);
+        assert!(backlog.is_empty());
+    }
+
+    #[test]
+    fn test_replay_is_bounded() {
+        let bus = EventBus::new();
+        for _ in 0..REPLAY_CAPACITY + 10 {
+            bus.publish("news.added", serde_json::Value::Null);
+        }
+
+        let (backlog, _) = bus.subscribe(Some(0));
+        assert_eq!(backlog.len(), REPLAY_CAPACITY);
+        assert_eq!(backlog[0].id, 11);
+    }
+}
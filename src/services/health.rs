@@ -0,0 +1,108 @@
+use log::warn;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex};
+use crate::config::{HealthConfig, Settings};
+
+static HEALTH: LazyLock<HealthRegistry> = LazyLock::new(|| {
+    let config = Settings::new().map(|settings| settings.health).unwrap_or_else(|e| {
+        warn!("Failed to load health configuration, using defaults: {}", e);
+        HealthConfig::default()
+    });
+    HealthRegistry::new(config.degraded_after)
+});
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Component {
+    Job,
+    Resolver,
+}
+
+/// Outcome history of one scheduler job or resolver. Timestamps are Unix seconds.
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct ComponentHealth {
+    pub last_success_at: Option<i64>,
+    pub last_failure_at: Option<i64>,
+    /// Start of the current run of failures, cleared by the next success.
+    pub failing_since: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+impl ComponentHealth {
+    pub fn is_degraded(&self, now: i64, degraded_after: i64) -> bool {
+        self.failing_since.is_some_and(|since| now - since >= degraded_after)
+    }
+}
+
+/// In-process record of the last outcome of each scheduler job and resolver.
+pub struct HealthRegistry {
+    jobs: Mutex<BTreeMap<String, ComponentHealth>>,
+    resolvers: Mutex<BTreeMap<String, ComponentHealth>>,
+    /// Seconds a component may keep failing before it is reported as degraded.
+    pub degraded_after: i64,
+}
+
+pub fn instance() -> &'static HealthRegistry {
+    &HEALTH
+}
+
+impl HealthRegistry {
+    pub fn new(degraded_after: u64) -> Self {
+        Self {
+            jobs: Mutex::new(BTreeMap::new()),
+            resolvers: Mutex::new(BTreeMap::new()),
+            degraded_after: degraded_after as i64,
+        }
+    }
+
+    fn components(&self, component: Component) -> std::sync::MutexGuard<'_, BTreeMap<String, ComponentHealth>> {
+        let components = match component {
+            Component::Job => &self.jobs,
+            Component::Resolver => &self.resolvers,
+        };
+        components.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn record_success(&self, component: Component, name: &str) {
+        let mut components = self.components(component);
+        let health = components.entry(name.to_string()).or_default();
+        health.last_success_at = Some(chrono::Utc::now().timestamp());
+        health.failing_since = None;
+    }
+
+    pub fn record_failure(&self, component: Component, name: &str, error: &str) {
+        let now = chrono::Utc::now().timestamp();
+        let mut components = self.components(component);
+        let health = components.entry(name.to_string()).or_default();
+        health.last_failure_at = Some(now);
+        health.failing_since.get_or_insert(now);
+        health.last_error = Some(error.to_string());
+    }
+
+    pub fn snapshot(&self, component: Component) -> BTreeMap<String, ComponentHealth> {
+        self.components(component).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failures_until_success() {
+        let registry = HealthRegistry::new(3600);
+        registry.record_failure(Component::Resolver, "Game8", "timed out");
+        registry.record_failure(Component::Resolver, "Game8", "timed out");
+
+        let game8 = registry.snapshot(Component::Resolver)["Game8"].clone();
+        let since = game8.failing_since.unwrap();
+        assert!(!game8.is_degraded(since + 3599, registry.degraded_after));
+        assert!(game8.is_degraded(since + 3600, registry.degraded_after));
+
+        registry.record_success(Component::Resolver, "Game8");
+        let game8 = registry.snapshot(Component::Resolver)["Game8"].clone();
+        assert_eq!(game8.failing_since, None);
+        assert_eq!(game8.last_error.as_deref(), Some("timed out"));
+        assert!(registry.snapshot(Component::Job).is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+use mongodb::event::cmap::CmapEvent;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::sync::LazyLock;
+use std::time::Duration;
+use super::response_cache;
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+const SCRAPE_BUCKETS: &[f64] = &[0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+const JOB_BUCKETS: &[f64] = &[1.0, 5.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0];
+
+/// Prometheus metrics for the HTTP server, scrapers, validator, scheduler and MongoDB pool.
+pub struct Metrics {
+    registry: Registry,
+    pub http_requests: IntCounterVec,
+    pub http_request_duration: HistogramVec,
+    pub rate_limited: IntCounter,
+    pub scrapes: IntCounterVec,
+    pub scrape_codes: IntGaugeVec,
+    pub scrape_duration: HistogramVec,
+    pub validations: IntCounterVec,
+    pub validation_queue_length: IntGauge,
+    pub drift_anomalies: IntCounterVec,
+    pub job_duration: HistogramVec,
+    pub mongo_connections: IntGauge,
+    pub mongo_connections_in_use: IntGauge,
+    pub mongo_checkout_failures: IntCounter,
+    pub mongo_pool_cleared: IntCounter,
+    cache_hits: IntCounter,
+    cache_misses: IntCounter,
+}
+
+pub fn instance() -> &'static Metrics {
+    &METRICS
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("starrail_api".to_string()), None)
+            .expect("Metric namespace is valid");
+
+        let metrics = Self {
+            http_requests: IntCounterVec::new(
+                Opts::new("http_requests_total", "HTTP requests by route, method and status"),
+                &["route", "method", "status"],
+            ).unwrap(),
+            http_request_duration: HistogramVec::new(
+                HistogramOpts::new("http_request_duration_seconds", "HTTP request latency by route"),
+                &["route", "method"],
+            ).unwrap(),
+            rate_limited: IntCounter::new("rate_limited_requests_total", "Requests rejected by the rate limiter").unwrap(),
+            scrapes: IntCounterVec::new(
+                Opts::new("scrapes_total", "Resolver scrapes by outcome"),
+                &["resolver", "outcome"],
+            ).unwrap(),
+            scrape_codes: IntGaugeVec::new(
+                Opts::new("scrape_codes", "Codes found by the last successful scrape of each resolver"),
+                &["resolver"],
+            ).unwrap(),
+            scrape_duration: HistogramVec::new(
+                HistogramOpts::new("scrape_duration_seconds", "Resolver scrape duration").buckets(SCRAPE_BUCKETS.to_vec()),
+                &["resolver"],
+            ).unwrap(),
+            validations: IntCounterVec::new(
+                Opts::new("validations_total", "Code validations by result"),
+                &["result"],
+            ).unwrap(),
+            validation_queue_length: IntGauge::new("validation_queue_length", "Codes waiting in the validation queue").unwrap(),
+            drift_anomalies: IntCounterVec::new(
+                Opts::new("drift_anomalies_total", "Anomalies reported by the scraper drift detector"),
+                &["resolver", "kind"],
+            ).unwrap(),
+            job_duration: HistogramVec::new(
+                HistogramOpts::new("job_duration_seconds", "Scheduled job duration").buckets(JOB_BUCKETS.to_vec()),
+                &["job", "outcome"],
+            ).unwrap(),
+            mongo_connections: IntGauge::new("mongodb_pool_connections", "Open MongoDB connections").unwrap(),
+            mongo_connections_in_use: IntGauge::new("mongodb_pool_connections_in_use", "MongoDB connections checked out of the pool").unwrap(),
+            mongo_checkout_failures: IntCounter::new("mongodb_pool_checkout_failures_total", "Failed MongoDB connection checkouts").unwrap(),
+            mongo_pool_cleared: IntCounter::new("mongodb_pool_cleared_total", "Times the MongoDB pool was cleared after an error").unwrap(),
+            cache_hits: IntCounter::new("response_cache_hits_total", "Responses served from the response cache").unwrap(),
+            cache_misses: IntCounter::new("response_cache_misses_total", "Responses built because the cache had no current entry").unwrap(),
+            registry,
+        };
+
+        let collectors: Vec<Box<dyn prometheus::core::Collector>> = vec![
+            Box::new(metrics.http_requests.clone()),
+            Box::new(metrics.http_request_duration.clone()),
+            Box::new(metrics.rate_limited.clone()),
+            Box::new(metrics.scrapes.clone()),
+            Box::new(metrics.scrape_codes.clone()),
+            Box::new(metrics.scrape_duration.clone()),
+            Box::new(metrics.validations.clone()),
+            Box::new(metrics.validation_queue_length.clone()),
+            Box::new(metrics.drift_anomalies.clone()),
+            Box::new(metrics.job_duration.clone()),
+            Box::new(metrics.mongo_connections.clone()),
+            Box::new(metrics.mongo_connections_in_use.clone()),
+            Box::new(metrics.mongo_checkout_failures.clone()),
+            Box::new(metrics.mongo_pool_cleared.clone()),
+            Box::new(metrics.cache_hits.clone()),
+            Box::new(metrics.cache_misses.clone()),
+        ];
+        for collector in collectors {
+            metrics.registry.register(collector).expect("Metric names are unique");
+        }
+
+        metrics
+    }
+
+    pub fn observe_request(&self, route: &str, method: &str, status: u16, elapsed: Duration) {
+        self.http_requests.with_label_values(&[route, method, &status.to_string()]).inc();
+        self.http_request_duration.with_label_values(&[route, method]).observe(elapsed.as_secs_f64());
+    }
+
+    pub fn observe_scrape(&self, resolver: &str, codes: Option<usize>, elapsed: Duration) {
+        let outcome = match codes {
+            Some(count) => {
+                self.scrape_codes.with_label_values(&[resolver]).set(count as i64);
+                "success"
+            },
+            None => "failure",
+        };
+        self.scrapes.with_label_values(&[resolver, outcome]).inc();
+        self.scrape_duration.with_label_values(&[resolver]).observe(elapsed.as_secs_f64());
+    }
+
+    pub fn observe_job(&self, job: &str, success: bool, elapsed: Duration) {
+        let outcome = if success { "success" } else { "failure" };
+        self.job_duration.with_label_values(&[job, outcome]).observe(elapsed.as_secs_f64());
+    }
+
+    /// Tracks the MongoDB connection pool; registered as the client's CMAP event handler.
+    pub fn observe_cmap_event(&self, event: CmapEvent) {
+        match event {
+            CmapEvent::ConnectionCreated(_) => self.mongo_connections.inc(),
+            CmapEvent::ConnectionClosed(_) => self.mongo_connections.dec(),
+            CmapEvent::ConnectionCheckedOut(_) => self.mongo_connections_in_use.inc(),
+            CmapEvent::ConnectionCheckedIn(_) => self.mongo_connections_in_use.dec(),
+            CmapEvent::ConnectionCheckoutFailed(_) => self.mongo_checkout_failures.inc(),
+            CmapEvent::PoolCleared(_) => self.mongo_pool_cleared.inc(),
+            _ => {},
+        }
+    }
+
+    /// Renders every metric in the Prometheus text format.
+    pub fn render(&self) -> Result<String, prometheus::Error> {
+        // The cache keeps its own counters; catch the exported ones up before encoding
+        let stats = response_cache::instance().stats();
+        self.cache_hits.inc_by(stats.hits.saturating_sub(self.cache_hits.get()));
+        self.cache_misses.inc_by(stats.misses.saturating_sub(self.cache_misses.get()));
+
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::new();
+        metrics.observe_request("/starrail/code", "GET", 200, Duration::from_millis(12));
+        metrics.observe_scrape("Game8", Some(7), Duration::from_secs(1));
+        metrics.observe_scrape("Fandom", None, Duration::from_secs(2));
+        metrics.validations.with_label_values(&["expired"]).inc();
+
+        let text = metrics.render().unwrap();
+        assert!(text.contains("starrail_api_http_requests_total{method=\"GET\",route=\"/starrail/code\",status=\"200\"} 1"));
+        assert!(text.contains("starrail_api_scrape_codes{resolver=\"Game8\"} 7"));
+        assert!(text.contains("starrail_api_scrapes_total{outcome=\"failure\",resolver=\"Fandom\"} 1"));
+        assert!(text.contains("starrail_api_validations_total{result=\"expired\"} 1"));
+    }
+}
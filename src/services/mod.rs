@@ -1,5 +1,16 @@
 pub mod code_service;
 pub mod db_service;
 pub mod validator_service;
+pub mod validator_pool;
+pub mod validation_queue;
 pub mod rate_limiter;
-pub mod news_service;
\ No newline at end of file
+pub mod news_service;
+pub mod storage;
+pub mod notification_service;
+pub mod event_bus;
+pub mod data_version;
+pub mod response_cache;
+pub mod metrics;
+pub mod health;
+pub mod source_tracker;
+pub mod drift_detector;
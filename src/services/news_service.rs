@@ -1,22 +1,49 @@
-use mongodb::{Collection, bson::{doc, Document}};
+use crate::config::Settings;
 use crate::resolvers::news::{NewsResolver, NewsItem};
-use log::{info, error, debug};
-use futures::TryStreamExt;
+use log::{error, debug};
 use futures::future::join_all;
-use crate::utils::lang_parser::{SUPPORTED_LANGUAGES, parse_language_code};
+use std::sync::Arc;
+use crate::utils::lang_parser::SUPPORTED_LANGUAGES;
+use serde::Deserialize;
+use super::data_version::{self, Dataset};
+use super::event_bus;
+use super::storage::{self, NewsQuery, Storage};
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventStatus {
+    Ongoing,
+    Upcoming,
+    Ended,
+}
+
+impl EventStatus {
+    pub fn matches(&self, start_at: Option<i64>, end_at: Option<i64>, now: i64) -> bool {
+        match self {
+            EventStatus::Ongoing => start_at.is_some_and(|start| start <= now) && end_at.is_some_and(|end| end > now),
+            EventStatus::Upcoming => start_at.is_some_and(|start| start > now),
+            EventStatus::Ended => end_at.is_some_and(|end| end <= now),
+        }
+    }
+}
 
 pub struct NewsService {
-    collection: Collection<NewsItem>,
+    storage: Arc<dyn Storage>,
     resolver: NewsResolver,
 }
 
 impl NewsService {
-    pub async fn new() -> Result<Self, mongodb::error::Error> {
-        let db_service = super::db_service::DbService::instance().await;
-        let collection = db_service.get_database().collection("news");
-        let resolver = NewsResolver::new();
-        
-        Ok(Self { collection, resolver })
+    pub async fn new() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let config = Settings::new()?;
+        let storage = storage::instance().await;
+        Ok(Self::with_storage(&config, storage))
+    }
+
+    /// Builds the service against the given storage, fetching news from the endpoint in `config`.
+    pub fn with_storage(config: &Settings, storage: Arc<dyn Storage>) -> Self {
+        let resolver = NewsResolver::with_client(&config.upstream.news, config.http.client("news"));
+
+        Self { storage, resolver }
     }
 
     pub async fn fetch_all_news(&self) -> Result<Vec<NewsItem>, Box<dyn std::error::Error + Send + Sync>> {
@@ -38,69 +65,36 @@ impl NewsService {
         Ok(all_news)
     }
 
-    pub async fn save_news(&self, news: &[NewsItem]) -> Result<(), mongodb::error::Error> {
-        debug!("Starting to save news items to database");
-        
-        for item in news {
-            let filter = doc! {
-                "id": &item.external_id,
-                "lang": &item.lang
-            };
-
-            let update = doc! {
-                "$set": {
-                    "id": &item.external_id,
-                    "title": &item.title,
-                    "description": &item.description,
-                    "createdAt": &item.created_at,
-                    "banner": &item.banner,
-                    "url": &item.url,
-                    "type": &item.news_type,
-                    "lang": &item.lang,
-                }
-            };
-
-            let update_result = self.collection
-                .update_one(filter, update)
-                .upsert(true)
-                .await?;
+    pub async fn save_news(&self, news: &[NewsItem]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let saved = self.storage.save_news(news).await?;
+        // An unchanged refresh keeps the cached responses and ETags
+        if saved.changed {
+            data_version::bump(Dataset::News);
+        }
 
-            if update_result.upserted_id.is_some() {
-                info!("New news item added: {} ({})", item.title, item.lang);
-            }
+        let bus = event_bus::instance();
+        for item in saved.added {
+            bus.publish("news.added", serde_json::to_value(&item)?);
         }
 
-        debug!("Successfully saved news items to database");
         Ok(())
     }
 
-    pub async fn get_news(&self, news_type: Option<&str>, lang: Option<&str>) 
-        -> Result<Vec<NewsItem>, mongodb::error::Error> {
-        let mut filter = Document::new();
-        
-        if let Some(type_str) = news_type {
-            filter.insert("type", type_str);
-        }
-        
-        let parsed_lang = lang.map_or("en-us", parse_language_code);
-        debug!("Filtering news by language code: {}", parsed_lang);
-        filter.insert("lang", parsed_lang);
+    /// News of `news_type` in `lang`, a full language code such as `ja-jp` as returned by
+    /// `parse_language_code`.
+    pub async fn get_news(&self, news_type: Option<&str>, lang: &str, status: Option<EventStatus>) 
+        -> Result<Vec<NewsItem>, Box<dyn std::error::Error + Send + Sync>> {
+        debug!("Filtering news by language code: {}", lang);
 
-        debug!("Applying MongoDB filter: {:?}", filter);
-
-        let mut cursor = self.collection
-            .find(filter)
-            .sort(doc! { "created_at": -1 })
-            .limit(20)
-            .await?;
-
-        let mut news = Vec::new();
-        
-        while let Some(item) = cursor.try_next().await? {
-            news.push(item);
-        }
+        let query = NewsQuery {
+            news_type,
+            lang,
+            status,
+            limit: 20,
+        };
+        let news = self.storage.get_news(&query).await?;
 
         debug!("Found {} news items (limited to 20)", news.len());
         Ok(news)
     }
-} 
\ No newline at end of file
+}
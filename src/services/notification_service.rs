@@ -0,0 +1,391 @@
+use log::{info, warn, error};
+use mongodb::bson::DateTime;
+use reqwest::Client;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+use crate::config::{WebhookConfig, WebhookFormat};
+use crate::resolvers::RedemptionCode;
+use super::drift_detector::DriftReport;
+use super::storage::Storage;
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeEvent {
+    Discovered,
+    Validated,
+    Deactivated,
+    /// A resolver's output changed shape, see `drift_detector`.
+    #[serde(rename = "drift")]
+    SourceDrift,
+}
+
+impl CodeEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CodeEvent::Discovered => "code.discovered",
+            CodeEvent::Validated => "code.validated",
+            CodeEvent::Deactivated => "code.deactivated",
+            CodeEvent::SourceDrift => "source.drift",
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            CodeEvent::Discovered => "New redemption code",
+            CodeEvent::Validated => "Redemption code is working",
+            CodeEvent::Deactivated => "Redemption code expired",
+            CodeEvent::SourceDrift => "Code source changed shape",
+        }
+    }
+
+    fn color(&self) -> u32 {
+        match self {
+            CodeEvent::Discovered => 0x5865F2,
+            CodeEvent::Validated => 0x57F287,
+            CodeEvent::Deactivated => 0xED4245,
+            CodeEvent::SourceDrift => 0xFEE75C,
+        }
+    }
+}
+
+/// One entry in the webhook delivery log.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebhookDelivery {
+    /// `redact_url` of the webhook URL, which often carries a token.
+    pub webhook: String,
+    pub event: String,
+    /// The code, or the resolver name for `source.drift`.
+    pub code: String,
+    pub attempts: u32,
+    pub success: bool,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub delivered_at: DateTime,
+}
+
+/// Sends code lifecycle events to the webhooks configured under `webhooks`.
+#[derive(Clone)]
+pub struct NotificationService {
+    client: Client,
+    webhooks: Arc<Vec<WebhookConfig>>,
+    storage: Arc<dyn Storage>,
+    retry_base_delay: Duration,
+}
+
+impl NotificationService {
+    pub fn new(webhooks: Vec<WebhookConfig>, storage: Arc<dyn Storage>) -> Self {
+        let client = Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        Self { client, webhooks: Arc::new(webhooks), storage, retry_base_delay: RETRY_BASE_DELAY }
+    }
+
+    /// Delivers `events` to every subscribed webhook in the background, in order.
+    pub fn notify(&self, events: &[CodeEvent], code: &RedemptionCode) {
+        for webhook in self.webhooks.iter() {
+            let subscribed: Vec<(CodeEvent, serde_json::Value)> = events.iter()
+                .filter(|event| webhook.events.contains(event))
+                .map(|&event| (event, build_payload(webhook.format, event, code)))
+                .collect();
+            self.spawn_deliveries(webhook, &code.code, subscribed);
+        }
+    }
+
+    pub fn notify_drift(&self, report: &DriftReport) {
+        for webhook in self.webhooks.iter().filter(|webhook| webhook.events.contains(&CodeEvent::SourceDrift)) {
+            let payload = build_drift_payload(webhook.format, report);
+            self.spawn_deliveries(webhook, &report.source, vec![(CodeEvent::SourceDrift, payload)]);
+        }
+    }
+
+    fn spawn_deliveries(&self, webhook: &WebhookConfig, subject: &str, deliveries: Vec<(CodeEvent, serde_json::Value)>) {
+        if deliveries.is_empty() {
+            return;
+        }
+
+        let service = self.clone();
+        let webhook = webhook.clone();
+        let subject = subject.to_string();
+
+        tokio::spawn(async move {
+            for (event, payload) in deliveries {
+                let delivery = service.deliver(&webhook, event, &subject, &payload).await;
+                if let Err(e) = service.storage.log_delivery(&delivery).await {
+                    error!("Failed to record webhook delivery: {}", e);
+                }
+            }
+        });
+    }
+
+    async fn deliver(&self, webhook: &WebhookConfig, event: CodeEvent, subject: &str, payload: &serde_json::Value) -> WebhookDelivery {
+        let mut attempts = 0;
+        let mut status = None;
+        let mut last_error = None;
+
+        while attempts <= webhook.retries {
+            if attempts > 0 {
+                tokio::time::sleep(self.retry_base_delay * (1 << (attempts - 1).min(5))).await;
+            }
+            attempts += 1;
+
+            match self.client.post(&webhook.url).json(payload).send().await {
+                Ok(response) if response.status().is_success() => {
+                    info!("Delivered {} for {} to webhook", event.name(), subject);
+                    return WebhookDelivery {
+                        webhook: redact_url(&webhook.url),
+                        event: event.name().to_string(),
+                        code: subject.to_string(),
+                        attempts,
+                        success: true,
+                        status: Some(response.status().as_u16()),
+                        error: None,
+                        delivered_at: DateTime::now(),
+                    };
+                },
+                Ok(response) => {
+                    let response_status = response.status();
+                    status = Some(response_status.as_u16());
+                    last_error = Some(format!("Webhook responded with {}", response_status));
+                    // Other client errors will not go away by retrying
+                    if response_status.is_client_error() && response_status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                        break;
+                    }
+                },
+                Err(e) => {
+                    status = None;
+                    // The error would otherwise repeat the URL
+                    last_error = Some(e.without_url().to_string());
+                },
+            }
+
+            warn!("Webhook delivery of {} for {} failed (attempt {}): {}",
+                event.name(), subject, attempts, last_error.as_deref().unwrap_or("unknown error"));
+        }
+
+        error!("Giving up on webhook delivery of {} for {} after {} attempts", event.name(), subject, attempts);
+        WebhookDelivery {
+            webhook: redact_url(&webhook.url),
+            event: event.name().to_string(),
+            code: subject.to_string(),
+            attempts,
+            success: false,
+            status,
+            error: last_error,
+            delivered_at: DateTime::now(),
+        }
+    }
+}
+
+/// The scheme and host of `url` and a short hash of the whole URL, enough to tell webhooks
+/// apart without keeping the secrets in their paths.
+pub fn redact_url(url: &str) -> String {
+    let hash = format!("{:x}", Sha256::digest(url.as_bytes()));
+    match reqwest::Url::parse(url) {
+        Ok(parsed) => format!("{}://{}/…#{}", parsed.scheme(), parsed.host_str().unwrap_or_default(), &hash[..12]),
+        Err(_) => format!("…#{}", &hash[..12]),
+    }
+}
+
+/// The JSON form of a code shared by webhooks and the live event stream.
+pub fn code_summary(code: &RedemptionCode) -> serde_json::Value {
+    serde_json::json!({
+        "code": code.code,
+        "rewards": code.rewards,
+        "items": code.items,
+        "source": code.source,
+        "server": code.server,
+        "active": code.active,
+    })
+}
+
+fn build_payload(format: WebhookFormat, event: CodeEvent, code: &RedemptionCode) -> serde_json::Value {
+    match format {
+        WebhookFormat::Json => serde_json::json!({
+            "event": event.name(),
+            "timestamp": chrono::Utc::now().timestamp(),
+            "code": code_summary(code),
+        }),
+        WebhookFormat::Discord => serde_json::json!({
+            "embeds": [{
+                "title": format!("{}: {}", event.title(), code.code),
+                "url": format!("https://hsr.hoyoverse.com/gift?code={}", code.code),
+                "description": code.rewards.join("\n"),
+                "color": event.color(),
+                "footer": { "text": format!("Source: {}", code.source) },
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+            }],
+        }),
+    }
+}
+
+fn build_drift_payload(format: WebhookFormat, report: &DriftReport) -> serde_json::Value {
+    let event = CodeEvent::SourceDrift;
+    match format {
+        WebhookFormat::Json => serde_json::json!({
+            "event": event.name(),
+            "timestamp": chrono::Utc::now().timestamp(),
+            "source": report.source,
+            "anomalies": report.anomalies,
+        }),
+        WebhookFormat::Discord => serde_json::json!({
+            "embeds": [{
+                "title": format!("{}: {}", event.title(), report.source),
+                "description": report.anomalies.iter()
+                    .map(|anomaly| format!("- {}", anomaly.describe()))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                "color": event.color(),
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+            }],
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolvers::Server;
+    use crate::services::storage::memory::MemoryStorage;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use wiremock::matchers::{method, path};
+
+    fn code() -> RedemptionCode {
+        RedemptionCode {
+            id: None,
+            code: "STARRAILGIFT".to_string(),
+            rewards: vec!["50 Stellar Jade".to_string(), "10000 Credit".to_string()],
+            items: Vec::new(),
+            source: "Game8".to_string(),
+            sources: Vec::new(),
+            active: true,
+            date: None,
+            first_seen_at: None,
+            last_seen_in_source_at: None,
+            last_validated_at: None,
+            deactivated_at: None,
+            server: Server::Global,
+            regions: Vec::new(),
+        }
+    }
+
+    // Real time rather than a paused clock, which would let the request timeout fire early
+    fn service(webhooks: Vec<WebhookConfig>, storage: Arc<dyn Storage>) -> NotificationService {
+        NotificationService {
+            retry_base_delay: Duration::from_millis(10),
+            ..NotificationService::new(webhooks, storage)
+        }
+    }
+
+    fn webhook(url: String, format: WebhookFormat) -> WebhookConfig {
+        WebhookConfig {
+            url,
+            format,
+            events: vec![CodeEvent::Discovered, CodeEvent::Validated, CodeEvent::Deactivated],
+            retries: 2,
+        }
+    }
+
+    #[test]
+    fn test_discord_payload() {
+        let payload = build_payload(WebhookFormat::Discord, CodeEvent::Discovered, &code());
+        let embed = &payload["embeds"][0];
+        assert_eq!(embed["title"], "New redemption code: STARRAILGIFT");
+        assert_eq!(embed["description"], "50 Stellar Jade\n10000 Credit");
+        assert_eq!(embed["footer"]["text"], "Source: Game8");
+    }
+
+    #[tokio::test]
+    async fn test_deliver_retries_server_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let service = service(Vec::new(), Arc::new(MemoryStorage::new()));
+        let webhook = webhook(format!("{}/hook", server.uri()), WebhookFormat::Json);
+        let payload = build_payload(webhook.format, CodeEvent::Discovered, &code());
+
+        let delivery = service.deliver(&webhook, CodeEvent::Discovered, "STARRAILGIFT", &payload).await;
+        assert!(delivery.success);
+        assert_eq!(delivery.attempts, 2);
+        assert_eq!(delivery.status, Some(204));
+    }
+
+    #[tokio::test]
+    async fn test_deliver_gives_up() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let service = service(Vec::new(), Arc::new(MemoryStorage::new()));
+        let webhook = webhook(format!("{}/hook", server.uri()), WebhookFormat::Json);
+        let payload = build_payload(webhook.format, CodeEvent::Deactivated, &code());
+
+        let delivery = service.deliver(&webhook, CodeEvent::Deactivated, "STARRAILGIFT", &payload).await;
+        assert!(!delivery.success);
+        assert_eq!(delivery.attempts, 3);
+        assert_eq!(delivery.status, Some(500));
+        assert_eq!(delivery.event, "code.deactivated");
+    }
+
+    #[tokio::test]
+    async fn test_notify_logs_subscribed_events() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::new());
+        let mut webhook = webhook(format!("{}/hook", server.uri()), WebhookFormat::Discord);
+        webhook.events = vec![CodeEvent::Deactivated];
+        let service = service(vec![webhook], storage.clone());
+
+        service.notify(&[CodeEvent::Discovered], &code());
+        service.notify(&[CodeEvent::Discovered, CodeEvent::Deactivated], &code());
+
+        let mut deliveries = Vec::new();
+        for _ in 0..50 {
+            deliveries = storage.get_deliveries(10).await.unwrap();
+            if !deliveries.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].event, "code.deactivated");
+        assert!(deliveries[0].success);
+        assert!(!deliveries[0].webhook.contains("/hook"));
+    }
+
+    #[test]
+    fn test_redact_url() {
+        let redacted = redact_url("https://discord.com/api/webhooks/123456/secret-token");
+        assert!(redacted.starts_with("https://discord.com/…#"));
+        assert!(!redacted.contains("secret-token"));
+        assert_eq!(redacted, redact_url("https://discord.com/api/webhooks/123456/secret-token"));
+        assert_ne!(redacted, redact_url("https://discord.com/api/webhooks/123456/other-token"));
+        assert!(!redact_url("not a url/secret-token").contains("secret-token"));
+    }
+}
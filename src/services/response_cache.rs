@@ -0,0 +1,144 @@
+use actix_web::web::Bytes;
+use log::warn;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use crate::config::{CacheConfig, Settings};
+use super::data_version::{Dataset, Version};
+
+/// Keys include request-controlled parts, so the cache is reset rather than allowed to grow unbounded.
+const MAX_ENTRIES: usize = 1024;
+
+static RESPONSE_CACHE: LazyLock<ResponseCache> = LazyLock::new(|| {
+    let config = Settings::new().map(|settings| settings.cache).unwrap_or_else(|e| {
+        warn!("Failed to load cache configuration, using defaults: {}", e);
+        CacheConfig::default()
+    });
+    ResponseCache::new(&config)
+});
+
+/// A serialized response body and the data version it was built from.
+pub struct CachedResponse {
+    pub version: u64,
+    pub modified_at: i64,
+    pub etag: String,
+    pub content_type: String,
+    pub body: Bytes,
+    stored_at: Instant,
+}
+
+impl CachedResponse {
+    pub fn new(version: Version, etag: String, content_type: &str, body: Bytes) -> Self {
+        Self {
+            version: version.number,
+            modified_at: version.modified_at,
+            etag,
+            content_type: content_type.to_string(),
+            body,
+            stored_at: Instant::now(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Read-through cache for code and news responses. Entries are tied to the data version
+/// of their dataset, so any write by the scheduler invalidates them immediately. The TTL
+/// bounds staleness when another instance writes to the same database.
+pub struct ResponseCache {
+    entries: Mutex<HashMap<String, Arc<CachedResponse>>>,
+    codes_ttl: Duration,
+    news_ttl: Duration,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+pub fn instance() -> &'static ResponseCache {
+    &RESPONSE_CACHE
+}
+
+impl ResponseCache {
+    pub fn new(config: &CacheConfig) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            codes_ttl: Duration::from_secs(config.codes_ttl),
+            news_ttl: Duration::from_secs(config.news_ttl),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn ttl(&self, dataset: Dataset) -> Duration {
+        match dataset {
+            Dataset::Codes => self.codes_ttl,
+            Dataset::News => self.news_ttl,
+        }
+    }
+
+    pub fn get(&self, dataset: Dataset, key: &str, version: Version) -> Option<Arc<CachedResponse>> {
+        let ttl = self.ttl(dataset);
+        let cached = self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(key)
+            .filter(|entry| entry.version == version.number && entry.stored_at.elapsed() < ttl)
+            .cloned();
+
+        let counter = if cached.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        cached
+    }
+
+    pub fn insert(&self, dataset: Dataset, key: String, response: CachedResponse) {
+        if self.ttl(dataset).is_zero() {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if entries.len() >= MAX_ENTRIES && !entries.contains_key(&key) {
+            entries.clear();
+        }
+        entries.insert(key, Arc::new(response));
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(number: u64) -> Version {
+        Version { number, modified_at: 1_760_745_600 }
+    }
+
+    fn response(number: u64) -> CachedResponse {
+        CachedResponse::new(version(number), "abc".to_string(), "application/json", Bytes::from_static(b"[]"))
+    }
+
+    #[test]
+    fn test_invalidated_by_new_version() {
+        let cache = ResponseCache::new(&CacheConfig::default());
+        cache.insert(Dataset::News, "news:event:en-us".to_string(), response(1));
+
+        assert!(cache.get(Dataset::News, "news:event:en-us", version(1)).is_some());
+        assert!(cache.get(Dataset::News, "news:event:en-us", version(2)).is_none());
+        assert!(cache.get(Dataset::News, "news:info:en-us", version(1)).is_none());
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 2 });
+    }
+
+    #[test]
+    fn test_zero_ttl_disables_cache() {
+        let cache = ResponseCache::new(&CacheConfig { codes_ttl: 0, ..CacheConfig::default() });
+        cache.insert(Dataset::Codes, "codes:en-us".to_string(), response(1));
+        assert!(cache.get(Dataset::Codes, "codes:en-us", version(1)).is_none());
+    }
+}
@@ -0,0 +1,291 @@
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{LazyLock, Mutex};
+use crate::config::{CircuitBreakerConfig, Settings};
+
+/// Scrapes kept per source for the yield history, about an hour at the default schedule.
+const YIELD_HISTORY: usize = 60;
+
+static SOURCE_TRACKER: LazyLock<SourceTracker> = LazyLock::new(|| {
+    let config = Settings::new().map(|settings| settings.circuit_breaker).unwrap_or_else(|e| {
+        warn!("Failed to load circuit breaker configuration, using defaults: {}", e);
+        CircuitBreakerConfig::default()
+    });
+    SourceTracker::new(config)
+});
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Scraped on every run.
+    Closed,
+    /// Skipped until `retry_at`.
+    Open,
+    /// One trial scrape is in flight after the backoff expired.
+    HalfOpen,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub struct YieldSample {
+    pub at: i64,
+    pub codes: usize,
+}
+
+/// Scrape history and circuit breaker state of one resolver. Timestamps are Unix seconds.
+#[derive(Debug, Serialize, Clone)]
+pub struct SourceStatus {
+    pub name: String,
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+    pub consecutive_empty: u32,
+    pub last_success_at: Option<i64>,
+    pub last_failure_at: Option<i64>,
+    pub last_error: Option<String>,
+    pub retry_at: Option<i64>,
+    /// Codes found by recent successful scrapes, oldest first.
+    pub yields: VecDeque<YieldSample>,
+    #[serde(skip)]
+    trips: u32,
+}
+
+impl SourceStatus {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            state: BreakerState::Closed,
+            consecutive_failures: 0,
+            consecutive_empty: 0,
+            last_success_at: None,
+            last_failure_at: None,
+            last_error: None,
+            retry_at: None,
+            yields: VecDeque::with_capacity(YIELD_HISTORY),
+            trips: 0,
+        }
+    }
+
+    pub fn average_yield(&self) -> Option<f64> {
+        if self.yields.is_empty() {
+            return None;
+        }
+        Some(self.yields.iter().map(|sample| sample.codes).sum::<usize>() as f64 / self.yields.len() as f64)
+    }
+}
+
+/// Tracks every resolver across scrape runs and backs off sources that keep failing or
+/// keep returning nothing, which usually means the page layout changed.
+pub struct SourceTracker {
+    sources: Mutex<BTreeMap<String, SourceStatus>>,
+    config: CircuitBreakerConfig,
+}
+
+pub fn instance() -> &'static SourceTracker {
+    &SOURCE_TRACKER
+}
+
+impl SourceTracker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self { sources: Mutex::new(BTreeMap::new()), config }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, SourceStatus>> {
+        self.sources.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Makes a source show up in the status list before its first scrape.
+    pub fn register(&self, name: &str) {
+        self.lock().entry(name.to_string()).or_insert_with(|| SourceStatus::new(name));
+    }
+
+    /// Whether `name` should be scraped now. An open breaker lets a single trial through once
+    /// its backoff has expired.
+    pub fn allow(&self, name: &str) -> bool {
+        self.allow_at(name, chrono::Utc::now().timestamp())
+    }
+
+    fn allow_at(&self, name: &str, now: i64) -> bool {
+        let mut sources = self.lock();
+        let source = sources.entry(name.to_string()).or_insert_with(|| SourceStatus::new(name));
+        match source.state {
+            BreakerState::Closed => true,
+            BreakerState::HalfOpen => false,
+            BreakerState::Open if source.retry_at.is_some_and(|retry_at| now >= retry_at) => {
+                info!("Retrying {} after backoff", name);
+                source.state = BreakerState::HalfOpen;
+                true
+            },
+            BreakerState::Open => false,
+        }
+    }
+
+    /// Records a scrape that returned `codes` codes, or failed with `error`.
+    pub fn record(&self, name: &str, result: Result<usize, &str>) {
+        self.record_at(name, result, chrono::Utc::now().timestamp());
+    }
+
+    fn record_at(&self, name: &str, result: Result<usize, &str>, now: i64) {
+        let empty_threshold = self.config.empty_threshold(name);
+        let mut sources = self.lock();
+        let source = sources.entry(name.to_string()).or_insert_with(|| SourceStatus::new(name));
+
+        let healthy = match result {
+            Ok(codes) => {
+                if source.yields.len() == YIELD_HISTORY {
+                    source.yields.pop_front();
+                }
+                source.yields.push_back(YieldSample { at: now, codes });
+                source.last_success_at = Some(now);
+                source.consecutive_failures = 0;
+                if codes == 0 {
+                    source.consecutive_empty += 1;
+                } else {
+                    source.consecutive_empty = 0;
+                }
+                codes > 0 || empty_threshold.is_none()
+            },
+            Err(error) => {
+                source.last_failure_at = Some(now);
+                source.last_error = Some(error.to_string());
+                source.consecutive_failures += 1;
+                false
+            },
+        };
+
+        if healthy {
+            if source.state != BreakerState::Closed {
+                info!("{} recovered, closing its circuit breaker", name);
+            }
+            source.state = BreakerState::Closed;
+            source.retry_at = None;
+            source.trips = 0;
+            return;
+        }
+
+        // The source answered but had nothing yet, which is no reason to wait longer than before
+        let empty_trial = source.state == BreakerState::HalfOpen && result.is_ok();
+        let tripped = source.state == BreakerState::HalfOpen
+            || source.consecutive_failures >= self.config.failure_threshold
+            || empty_threshold.is_some_and(|threshold| source.consecutive_empty >= threshold);
+        if tripped && source.state != BreakerState::Open {
+            let doublings = if empty_trial { source.trips.saturating_sub(1) } else { source.trips };
+            let backoff = self.config.backoff
+                .saturating_mul(1 << doublings.min(16))
+                .min(self.config.max_backoff);
+            if !empty_trial {
+                source.trips += 1;
+            }
+            source.state = BreakerState::Open;
+            source.retry_at = Some(now + backoff as i64);
+            warn!("Opening circuit breaker for {} for {}s ({} failures, {} empty results in a row)",
+                name, backoff, source.consecutive_failures, source.consecutive_empty);
+        }
+    }
+
+    pub fn snapshot(&self) -> Vec<SourceStatus> {
+        self.lock().values().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BreakerOverrides;
+    use std::collections::HashMap;
+
+    fn tracker() -> SourceTracker {
+        SourceTracker::new(CircuitBreakerConfig {
+            failure_threshold: 3,
+            empty_threshold: 5,
+            backoff: 300,
+            max_backoff: 1000,
+            sources: HashMap::from([("hoyolab".to_string(), BreakerOverrides { empty_threshold: Some(0) })]),
+        })
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let tracker = tracker();
+        for now in 0..3 {
+            assert!(tracker.allow_at("Eurogamer", now));
+            tracker.record_at("Eurogamer", Err("HTTP 500"), now);
+        }
+
+        assert!(!tracker.allow_at("Eurogamer", 299));
+        let status = &tracker.snapshot()[0];
+        assert_eq!(status.state, BreakerState::Open);
+        assert_eq!(status.retry_at, Some(302));
+
+        // One trial after the backoff; failing it doubles the backoff
+        assert!(tracker.allow_at("Eurogamer", 302));
+        assert!(!tracker.allow_at("Eurogamer", 302));
+        tracker.record_at("Eurogamer", Err("HTTP 500"), 303);
+        assert_eq!(tracker.snapshot()[0].retry_at, Some(903));
+
+        // Capped at max_backoff
+        assert!(tracker.allow_at("Eurogamer", 903));
+        tracker.record_at("Eurogamer", Err("HTTP 500"), 903);
+        assert_eq!(tracker.snapshot()[0].retry_at, Some(1903));
+
+        assert!(tracker.allow_at("Eurogamer", 1903));
+        tracker.record_at("Eurogamer", Ok(4), 1903);
+        let status = &tracker.snapshot()[0];
+        assert_eq!(status.state, BreakerState::Closed);
+        assert_eq!(status.consecutive_failures, 0);
+        assert!(tracker.allow_at("Eurogamer", 1904));
+    }
+
+    #[test]
+    fn test_opens_after_empty_results() {
+        let tracker = tracker();
+        tracker.record_at("Polygon", Ok(6), 0);
+        for now in 1..5 {
+            tracker.record_at("Polygon", Ok(0), now);
+        }
+        assert!(tracker.allow_at("Polygon", 5));
+
+        tracker.record_at("Polygon", Ok(0), 5);
+        let status = &tracker.snapshot()[0];
+        assert_eq!(status.state, BreakerState::Open);
+        assert_eq!(status.consecutive_empty, 5);
+        assert_eq!(status.average_yield(), Some(1.0));
+    }
+
+    #[test]
+    fn test_empty_trial_keeps_backoff() {
+        let tracker = tracker();
+        for now in 0..5 {
+            tracker.record_at("Polygon", Ok(0), now);
+        }
+        assert_eq!(tracker.snapshot()[0].retry_at, Some(304));
+
+        // The trial found nothing again: reopened for the same backoff, not twice as long
+        for trial in [304, 604, 904] {
+            assert!(tracker.allow_at("Polygon", trial));
+            tracker.record_at("Polygon", Ok(0), trial);
+            let status = &tracker.snapshot()[0];
+            assert_eq!(status.state, BreakerState::Open);
+            assert_eq!(status.retry_at, Some(trial + 300));
+        }
+    }
+
+    #[test]
+    fn test_empty_threshold_opt_out() {
+        let tracker = tracker();
+        for now in 0..20 {
+            assert!(tracker.allow_at("Hoyolab", now));
+            tracker.record_at("Hoyolab", Ok(0), now);
+        }
+        let status = &tracker.snapshot()[0];
+        assert_eq!(status.state, BreakerState::Closed);
+        assert_eq!(status.consecutive_empty, 20);
+
+        // Failures still open the breaker, and an empty trial closes it again
+        for now in 20..23 {
+            tracker.record_at("Hoyolab", Err("HTTP 500"), now);
+        }
+        assert!(tracker.allow_at("Hoyolab", 322));
+        tracker.record_at("Hoyolab", Ok(0), 322);
+        assert_eq!(tracker.snapshot()[0].state, BreakerState::Closed);
+    }
+}
@@ -0,0 +1,313 @@
+use async_trait::async_trait;
+use mongodb::bson::DateTime;
+use tokio::sync::RwLock;
+use log::{info, debug};
+use crate::resolvers::{RedemptionCode, RegionStatus};
+use crate::resolvers::news::NewsItem;
+use crate::services::notification_service::WebhookDelivery;
+use crate::services::validation_queue::QueuedValidation;
+use crate::services::validator_service::ValidationAttempt;
+use super::{CodeRepository, DeliveryLogRepository, HealthCheck, NewsRepository, NewsQuery, SavedNews, StorageResult, ValidationLogRepository, ValidationQueueRepository, merge_scraped_code, new_stored_code};
+
+/// Storage backend that keeps everything in process memory, for tests and local development.
+pub struct MemoryStorage {
+    codes: RwLock<Vec<RedemptionCode>>,
+    news: RwLock<Vec<NewsItem>>,
+    deliveries: RwLock<Vec<WebhookDelivery>>,
+    validations: RwLock<Vec<ValidationAttempt>>,
+    validation_queue: RwLock<Vec<QueuedValidation>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self {
+            codes: RwLock::new(Vec::new()),
+            news: RwLock::new(Vec::new()),
+            deliveries: RwLock::new(Vec::new()),
+            validations: RwLock::new(Vec::new()),
+            validation_queue: RwLock::new(Vec::new()),
+        }
+    }
+}
+
+impl Default for MemoryStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl CodeRepository for MemoryStorage {
+    async fn save_codes(&self, codes: &[RedemptionCode]) -> StorageResult<bool> {
+        let mut stored = self.codes.write().await;
+        let mut changed = false;
+
+        for code in codes {
+            let now = DateTime::now();
+            match stored.iter_mut().find(|existing| existing.code == code.code) {
+                Some(existing) => changed |= merge_scraped_code(existing, code, now),
+                None => {
+                    stored.push(new_stored_code(code, now));
+                    changed = true;
+                },
+            }
+        }
+
+        debug!("Saved {} codes to memory", codes.len());
+        Ok(changed)
+    }
+
+    async fn get_codes(&self) -> StorageResult<(Vec<RedemptionCode>, Vec<RedemptionCode>)> {
+        let stored = self.codes.read().await;
+        let (active, inactive) = stored.iter().cloned().partition(|code| code.active);
+        Ok((active, inactive))
+    }
+
+    async fn get_code(&self, code: &str) -> StorageResult<Option<RedemptionCode>> {
+        let stored = self.codes.read().await;
+        Ok(stored.iter().find(|existing| existing.code == code).cloned())
+    }
+
+    async fn update_code_status(&self, code: &str, active: bool) -> StorageResult<()> {
+        let mut stored = self.codes.write().await;
+        if let Some(existing) = stored.iter_mut().find(|existing| existing.code == code) {
+            existing.active = active;
+            existing.deactivated_at = if active { None } else { Some(DateTime::now()) };
+        }
+
+        info!("Updated code {} status to active={}", code, active);
+        Ok(())
+    }
+
+    async fn mark_validated(&self, code: &str) -> StorageResult<()> {
+        let mut stored = self.codes.write().await;
+        if let Some(existing) = stored.iter_mut().find(|existing| existing.code == code) {
+            existing.last_validated_at = Some(DateTime::now());
+        }
+
+        Ok(())
+    }
+
+    async fn update_code_regions(&self, code: &str, regions: &[RegionStatus]) -> StorageResult<()> {
+        let mut stored = self.codes.write().await;
+        if let Some(existing) = stored.iter_mut().find(|existing| existing.code == code) {
+            existing.regions = regions.to_vec();
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NewsRepository for MemoryStorage {
+    async fn save_news(&self, news: &[NewsItem]) -> StorageResult<SavedNews> {
+        let mut stored = self.news.write().await;
+        let mut saved = SavedNews::default();
+
+        for item in news {
+            match stored.iter_mut().find(|existing| existing.external_id == item.external_id && existing.lang == item.lang) {
+                Some(existing) if existing == item => {},
+                Some(existing) => {
+                    *existing = item.clone();
+                    saved.changed = true;
+                },
+                None => {
+                    info!("New news item added: {} ({})", item.title, item.lang);
+                    stored.push(item.clone());
+                    saved.added.push(item.clone());
+                    saved.changed = true;
+                },
+            }
+        }
+
+        Ok(saved)
+    }
+
+    async fn get_news(&self, query: &NewsQuery<'_>) -> StorageResult<Vec<NewsItem>> {
+        let stored = self.news.read().await;
+        let now = chrono::Utc::now().timestamp();
+
+        let mut news: Vec<NewsItem> = stored.iter()
+            .filter(|item| item.lang == query.lang)
+            .filter(|item| query.news_type.is_none_or(|news_type| item.news_type == news_type))
+            .filter(|item| query.status.is_none_or(|status| status.matches(item.start_at, item.end_at, now)))
+            .cloned()
+            .collect();
+
+        news.sort_by_key(|item| std::cmp::Reverse(item.created_at));
+        news.truncate(query.limit);
+
+        Ok(news)
+    }
+}
+
+#[async_trait]
+impl DeliveryLogRepository for MemoryStorage {
+    async fn log_delivery(&self, delivery: &WebhookDelivery) -> StorageResult<()> {
+        self.deliveries.write().await.push(delivery.clone());
+        Ok(())
+    }
+
+    async fn get_deliveries(&self, limit: usize) -> StorageResult<Vec<WebhookDelivery>> {
+        let stored = self.deliveries.read().await;
+        Ok(stored.iter().rev().take(limit).cloned().collect())
+    }
+}
+
+#[async_trait]
+impl ValidationLogRepository for MemoryStorage {
+    async fn log_validation(&self, attempt: &ValidationAttempt) -> StorageResult<()> {
+        self.validations.write().await.push(attempt.clone());
+        Ok(())
+    }
+
+    async fn get_validations(&self, code: &str, limit: usize) -> StorageResult<Vec<ValidationAttempt>> {
+        let stored = self.validations.read().await;
+        Ok(stored.iter().rev().filter(|attempt| attempt.code == code).take(limit).cloned().collect())
+    }
+}
+
+#[async_trait]
+impl ValidationQueueRepository for MemoryStorage {
+    async fn enqueue_validation(&self, entry: &QueuedValidation) -> StorageResult<bool> {
+        let mut queue = self.validation_queue.write().await;
+        if queue.iter().any(|queued| queued.code == entry.code) {
+            return Ok(false);
+        }
+        queue.push(entry.clone());
+        Ok(true)
+    }
+
+    async fn update_queued_validation(&self, entry: &QueuedValidation) -> StorageResult<()> {
+        let mut queue = self.validation_queue.write().await;
+        queue.retain(|queued| queued.code != entry.code);
+        queue.push(entry.clone());
+        Ok(())
+    }
+
+    async fn remove_queued_validation(&self, code: &str) -> StorageResult<()> {
+        self.validation_queue.write().await.retain(|queued| queued.code != code);
+        Ok(())
+    }
+
+    async fn get_validation_queue(&self) -> StorageResult<Vec<QueuedValidation>> {
+        Ok(self.validation_queue.read().await.clone())
+    }
+}
+
+#[async_trait]
+impl HealthCheck for MemoryStorage {
+    async fn ping(&self) -> StorageResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolvers::{CodeSource, Server};
+    use crate::services::news_service::EventStatus;
+
+    fn scraped_code(code: &str, source: &str) -> RedemptionCode {
+        let rewards = vec!["50 Stellar Jade".to_string()];
+        RedemptionCode {
+            id: None,
+            code: code.to_string(),
+            rewards: rewards.clone(),
+            items: Vec::new(),
+            source: source.to_string(),
+            sources: vec![CodeSource::new(source, &rewards, DateTime::now())],
+            active: true,
+            date: None,
+            first_seen_at: None,
+            last_seen_in_source_at: None,
+            last_validated_at: None,
+            deactivated_at: None,
+            server: Server::Global,
+            regions: Vec::new(),
+        }
+    }
+
+    fn news_item(id: &str, news_type: &str, created_at: i64, start_at: Option<i64>, end_at: Option<i64>) -> NewsItem {
+        NewsItem {
+            id: None,
+            external_id: id.to_string(),
+            title: format!("News {}", id),
+            description: String::new(),
+            created_at,
+            banner: None,
+            url: format!("https://www.hoyolab.com/article/{}", id),
+            news_type: news_type.to_string(),
+            lang: "en-us".to_string(),
+            start_at,
+            end_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_codes_merges_sources() {
+        let storage = MemoryStorage::new();
+        assert!(storage.save_codes(&[scraped_code("STARRAILGIFT", "Game8")]).await.unwrap());
+        assert!(storage.save_codes(&[scraped_code("STARRAILGIFT", "Prydwen")]).await.unwrap());
+        // Seeing the same code in the same source again changes nothing
+        assert!(!storage.save_codes(&[scraped_code("STARRAILGIFT", "Prydwen")]).await.unwrap());
+
+        let code = storage.get_code("STARRAILGIFT").await.unwrap().unwrap();
+        assert_eq!(code.source, "Prydwen");
+        assert_eq!(code.sources.len(), 2);
+        assert!(code.first_seen_at.is_some());
+        assert!(code.last_seen_in_source_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_update_code_status() {
+        let storage = MemoryStorage::new();
+        storage.save_codes(&[scraped_code("STARRAILGIFT", "Game8")]).await.unwrap();
+        storage.mark_validated("STARRAILGIFT").await.unwrap();
+        storage.update_code_status("STARRAILGIFT", false).await.unwrap();
+
+        let (active, inactive) = storage.get_codes().await.unwrap();
+        assert!(active.is_empty());
+        assert_eq!(inactive.len(), 1);
+        assert!(inactive[0].last_validated_at.is_some());
+        assert!(inactive[0].deactivated_at.is_some());
+
+        // A later scrape must not resurrect the code
+        storage.save_codes(&[scraped_code("STARRAILGIFT", "Game8")]).await.unwrap();
+        let (active, _) = storage.get_codes().await.unwrap();
+        assert!(active.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_news_filters_and_sorts() {
+        let storage = MemoryStorage::new();
+        let now = chrono::Utc::now().timestamp();
+        let saved = storage.save_news(&[
+            news_item("1", "event", 100, Some(now - 60), Some(now + 60)),
+            news_item("2", "event", 300, Some(now + 60), Some(now + 120)),
+            news_item("3", "event", 200, Some(now - 120), Some(now - 60)),
+            news_item("4", "notice", 400, None, None),
+        ]).await.unwrap();
+        assert_eq!(saved.added.len(), 4);
+        let saved = storage.save_news(&[news_item("1", "event", 100, Some(now - 60), Some(now + 60))]).await.unwrap();
+        assert!(saved.added.is_empty());
+        assert!(!saved.changed);
+        // A moved end date is a change, but not a new item
+        let saved = storage.save_news(&[news_item("1", "event", 100, Some(now - 60), Some(now + 90))]).await.unwrap();
+        assert!(saved.added.is_empty());
+        assert!(saved.changed);
+
+        let query = NewsQuery { news_type: Some("event"), lang: "en-us", status: None, limit: 20 };
+        let events = storage.get_news(&query).await.unwrap();
+        let ids: Vec<&str> = events.iter().map(|item| item.external_id.as_str()).collect();
+        assert_eq!(ids, vec!["2", "3", "1"]);
+
+        let query = NewsQuery { news_type: Some("event"), lang: "en-us", status: Some(EventStatus::Ongoing), limit: 20 };
+        let ongoing = storage.get_news(&query).await.unwrap();
+        assert_eq!(ongoing.len(), 1);
+        assert_eq!(ongoing[0].external_id, "1");
+
+        let query = NewsQuery { news_type: None, lang: "ja-jp", status: None, limit: 20 };
+        assert!(storage.get_news(&query).await.unwrap().is_empty());
+    }
+}
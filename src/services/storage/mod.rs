@@ -0,0 +1,183 @@
+use async_trait::async_trait;
+use mongodb::bson::DateTime;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+use log::{info, error};
+use crate::config::{Settings, StorageBackend};
+use crate::resolvers::{CodeSource, RedemptionCode, RegionStatus, Server};
+use crate::rewards::Reward;
+use crate::resolvers::news::NewsItem;
+use super::db_service::DbService;
+use super::news_service::EventStatus;
+use super::notification_service::WebhookDelivery;
+use super::validation_queue::QueuedValidation;
+use super::validator_service::ValidationAttempt;
+
+pub mod memory;
+pub mod sqlite;
+
+pub type StorageResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+static STORAGE_INSTANCE: OnceCell<Arc<dyn Storage>> = OnceCell::const_new();
+
+#[async_trait]
+pub trait CodeRepository: Send + Sync {
+    /// Upserts `codes` and returns whether any was added or changed. Sightings that only move
+    /// the last-seen times do not count as a change.
+    async fn save_codes(&self, codes: &[RedemptionCode]) -> StorageResult<bool>;
+    async fn get_codes(&self) -> StorageResult<(Vec<RedemptionCode>, Vec<RedemptionCode>)>;
+    async fn get_code(&self, code: &str) -> StorageResult<Option<RedemptionCode>>;
+    async fn update_code_status(&self, code: &str, active: bool) -> StorageResult<()>;
+    async fn mark_validated(&self, code: &str) -> StorageResult<()>;
+    /// Replaces the per-region statuses of `code`.
+    async fn update_code_regions(&self, code: &str, regions: &[RegionStatus]) -> StorageResult<()>;
+}
+
+/// Outcome of `NewsRepository::save_news`.
+#[derive(Debug, Default)]
+pub struct SavedNews {
+    /// Items that were not stored before.
+    pub added: Vec<NewsItem>,
+    /// Whether any item was added or differs from the stored copy.
+    pub changed: bool,
+}
+
+#[async_trait]
+pub trait NewsRepository: Send + Sync {
+    /// Upserts `news`, reporting the items that were not stored before.
+    async fn save_news(&self, news: &[NewsItem]) -> StorageResult<SavedNews>;
+    async fn get_news(&self, query: &NewsQuery<'_>) -> StorageResult<Vec<NewsItem>>;
+}
+
+#[async_trait]
+pub trait DeliveryLogRepository: Send + Sync {
+    async fn log_delivery(&self, delivery: &WebhookDelivery) -> StorageResult<()>;
+    /// Most recent deliveries first.
+    #[allow(dead_code)]
+    async fn get_deliveries(&self, limit: usize) -> StorageResult<Vec<WebhookDelivery>>;
+}
+
+#[async_trait]
+pub trait ValidationLogRepository: Send + Sync {
+    async fn log_validation(&self, attempt: &ValidationAttempt) -> StorageResult<()>;
+    /// Most recent attempts for `code` first.
+    async fn get_validations(&self, code: &str, limit: usize) -> StorageResult<Vec<ValidationAttempt>>;
+}
+
+/// The validation queue, one entry per code. See `validation_queue`.
+#[async_trait]
+pub trait ValidationQueueRepository: Send + Sync {
+    /// Queues `entry` unless its code is already queued. Returns whether it was added.
+    async fn enqueue_validation(&self, entry: &QueuedValidation) -> StorageResult<bool>;
+    /// Replaces the queued entry of the same code.
+    async fn update_queued_validation(&self, entry: &QueuedValidation) -> StorageResult<()>;
+    async fn remove_queued_validation(&self, code: &str) -> StorageResult<()>;
+    async fn get_validation_queue(&self) -> StorageResult<Vec<QueuedValidation>>;
+}
+
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    /// Round trip to the backend, used by the readiness endpoint.
+    async fn ping(&self) -> StorageResult<()>;
+}
+
+pub trait Storage: CodeRepository + NewsRepository + DeliveryLogRepository + ValidationLogRepository + ValidationQueueRepository + HealthCheck {}
+
+impl<T: CodeRepository + NewsRepository + DeliveryLogRepository + ValidationLogRepository + ValidationQueueRepository + HealthCheck> Storage for T {}
+
+pub struct NewsQuery<'a> {
+    pub news_type: Option<&'a str>,
+    pub lang: &'a str,
+    pub status: Option<EventStatus>,
+    pub limit: usize,
+}
+
+/// Returns the storage backend selected by `storage.backend` in the config.
+pub async fn instance() -> Arc<dyn Storage> {
+    STORAGE_INSTANCE.get_or_init(|| async {
+        let config = Settings::new().expect("Failed to load configuration");
+        match config.storage.backend {
+            StorageBackend::Mongodb => {
+                let storage: Arc<dyn Storage> = DbService::instance().await;
+                storage
+            },
+            StorageBackend::Memory => {
+                info!("Using in-memory storage, data will not persist across restarts");
+                Arc::new(memory::MemoryStorage::new())
+            },
+            StorageBackend::Sqlite => {
+                match sqlite::SqliteStorage::open(&config.sqlite.path) {
+                    Ok(storage) => Arc::new(storage),
+                    Err(e) => {
+                        error!("Failed to open SQLite database {}: {}", config.sqlite.path, e);
+                        panic!("Database initialization failed");
+                    }
+                }
+            },
+        }
+    }).await.clone()
+}
+
+/// Builds the stored form of a code that has never been seen before.
+pub fn new_stored_code(scraped: &RedemptionCode, now: DateTime) -> RedemptionCode {
+    RedemptionCode {
+        id: None,
+        date: Some(now),
+        first_seen_at: Some(now),
+        last_seen_in_source_at: Some(now),
+        ..scraped.clone()
+    }
+}
+
+/// Applies a fresh scrape on top of a stored code. Fields owned by validation
+/// (`active`, `last_validated_at`, `deactivated_at`) are left untouched. Returns whether
+/// anything but the last-seen times changed.
+pub fn merge_scraped_code(existing: &mut RedemptionCode, scraped: &RedemptionCode, now: DateTime) -> bool {
+    let before = ScrapedContent::of(existing);
+
+    // Codes saved before per-source tracking only know their last source
+    if existing.sources.is_empty() && !existing.source.is_empty() {
+        let seen_at = existing.date.unwrap_or(now);
+        existing.sources.push(CodeSource::new(&existing.source, &existing.rewards, seen_at));
+    }
+    for sighting in &scraped.sources {
+        existing.record_source(sighting.clone());
+    }
+
+    existing.rewards = scraped.rewards.clone();
+    existing.items = scraped.items.clone();
+    existing.source = scraped.source.clone();
+    existing.server = scraped.server;
+    existing.last_seen_in_source_at = Some(now);
+    if existing.first_seen_at.is_none() {
+        existing.first_seen_at = Some(existing.date.unwrap_or(now));
+    }
+
+    ScrapedContent::of(existing) != before
+}
+
+/// What a scrape can change on a stored code, leaving out the last-seen times.
+#[derive(PartialEq)]
+struct ScrapedContent {
+    rewards: Vec<String>,
+    items: Vec<Reward>,
+    source: String,
+    server: Server,
+    sources: Vec<(String, DateTime, Vec<String>)>,
+    first_seen_at: Option<DateTime>,
+}
+
+impl ScrapedContent {
+    fn of(code: &RedemptionCode) -> Self {
+        Self {
+            rewards: code.rewards.clone(),
+            items: code.items.clone(),
+            source: code.source.clone(),
+            server: code.server,
+            sources: code.sources.iter()
+                .map(|source| (source.name.clone(), source.first_seen_at, source.rewards.clone()))
+                .collect(),
+            first_seen_at: code.first_seen_at,
+        }
+    }
+}
@@ -0,0 +1,799 @@
+use async_trait::async_trait;
+use mongodb::bson::DateTime;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use log::{info, debug};
+use crate::resolvers::{CodeSource, RedemptionCode, RegionStatus, Server};
+use crate::resolvers::news::NewsItem;
+use crate::services::news_service::EventStatus;
+use crate::services::notification_service::WebhookDelivery;
+use crate::services::validation_queue::{QueuedValidation, ValidationPriority};
+use crate::services::validator_service::ValidationAttempt;
+use super::{CodeRepository, DeliveryLogRepository, HealthCheck, NewsRepository, NewsQuery, SavedNews, StorageResult, ValidationLogRepository, ValidationQueueRepository, merge_scraped_code, new_stored_code};
+
+// Each entry moves the schema one version forward; never edit an entry once released.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE codes (
+        code TEXT PRIMARY KEY,
+        rewards TEXT NOT NULL,
+        items TEXT NOT NULL,
+        source TEXT NOT NULL,
+        sources TEXT NOT NULL,
+        active INTEGER NOT NULL,
+        date INTEGER,
+        first_seen_at INTEGER,
+        last_seen_in_source_at INTEGER,
+        last_validated_at INTEGER,
+        deactivated_at INTEGER
+    );
+    CREATE TABLE news (
+        id TEXT NOT NULL,
+        lang TEXT NOT NULL,
+        title TEXT NOT NULL,
+        description TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        banner TEXT,
+        url TEXT NOT NULL,
+        type TEXT NOT NULL,
+        start_at INTEGER,
+        end_at INTEGER,
+        PRIMARY KEY (id, lang)
+    );
+    CREATE INDEX news_lang_type_created_at ON news (lang, type, created_at DESC);",
+    "CREATE TABLE webhook_deliveries (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        url TEXT NOT NULL,
+        event TEXT NOT NULL,
+        code TEXT NOT NULL,
+        attempts INTEGER NOT NULL,
+        success INTEGER NOT NULL,
+        status INTEGER,
+        error TEXT,
+        delivered_at INTEGER NOT NULL
+    );",
+    "CREATE TABLE validations (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        code TEXT NOT NULL,
+        uid TEXT NOT NULL,
+        retcode INTEGER,
+        message TEXT NOT NULL,
+        result TEXT NOT NULL,
+        validated_at INTEGER NOT NULL
+    );
+    CREATE INDEX validations_code ON validations (code, id DESC);",
+    "CREATE TABLE validation_queue (
+        code TEXT PRIMARY KEY,
+        priority TEXT NOT NULL,
+        waiting_since INTEGER NOT NULL,
+        not_before INTEGER NOT NULL,
+        retries INTEGER NOT NULL
+    );",
+    "ALTER TABLE codes ADD COLUMN server TEXT NOT NULL DEFAULT 'global';",
+    "ALTER TABLE codes ADD COLUMN regions TEXT NOT NULL DEFAULT '[]';
+    ALTER TABLE validations ADD COLUMN region TEXT NOT NULL DEFAULT '';",
+    // Deliveries logged before this kept the full webhook URL, secrets included
+    "ALTER TABLE webhook_deliveries RENAME COLUMN url TO webhook;
+    UPDATE webhook_deliveries SET webhook = 'redacted';",
+];
+
+const CODE_COLUMNS: &str = "code, rewards, items, source, sources, active, date, \
+    first_seen_at, last_seen_in_source_at, last_validated_at, deactivated_at, server, regions";
+
+const NEWS_COLUMNS: &str = "id, lang, title, description, created_at, banner, url, type, start_at, end_at";
+
+// bson::DateTime has no plain JSON form, so sources are stored with millisecond timestamps
+#[derive(Serialize, Deserialize)]
+struct StoredSource {
+    name: String,
+    first_seen_at: i64,
+    last_seen_at: i64,
+    rewards: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredRegion {
+    region: String,
+    valid: bool,
+    checked_at: i64,
+}
+
+/// Embedded SQLite storage backend for small self-hosted deployments.
+pub struct SqliteStorage {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStorage {
+    pub fn open(path: &str) -> Result<Self, rusqlite::Error> {
+        let mut conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        migrate(&mut conn)?;
+
+        info!("Opened SQLite database at {}", path);
+        Ok(Self { conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    async fn with_connection<T, F>(&self, f: F) -> StorageResult<T>
+    where
+        F: FnOnce(&mut Connection) -> Result<T, rusqlite::Error> + Send + 'static,
+        T: Send + 'static,
+    {
+        let conn = self.conn.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            f(&mut conn)
+        }).await?;
+
+        Ok(result?)
+    }
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), rusqlite::Error> {
+    let current: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(current) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", version + 1)?;
+        tx.commit()?;
+        info!("Applied SQLite migration {}", version + 1);
+    }
+
+    Ok(())
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, rusqlite::Error> {
+    serde_json::to_string(value).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+fn from_json<T: for<'de> Deserialize<'de>>(column: usize, json: &str) -> Result<T, rusqlite::Error> {
+    serde_json::from_str(json)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(column, rusqlite::types::Type::Text, Box::new(e)))
+}
+
+fn millis(date: Option<DateTime>) -> Option<i64> {
+    date.map(|date| date.timestamp_millis())
+}
+
+fn code_from_row(row: &Row) -> Result<RedemptionCode, rusqlite::Error> {
+    let sources: Vec<StoredSource> = from_json(4, &row.get::<_, String>(4)?)?;
+    let regions: Vec<StoredRegion> = from_json(12, &row.get::<_, String>(12)?)?;
+    let server: String = row.get(11)?;
+    let server = Server::parse(&server).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(11, rusqlite::types::Type::Text, format!("unknown server {}", server).into())
+    })?;
+
+    Ok(RedemptionCode {
+        id: None,
+        code: row.get(0)?,
+        rewards: from_json(1, &row.get::<_, String>(1)?)?,
+        items: from_json(2, &row.get::<_, String>(2)?)?,
+        source: row.get(3)?,
+        sources: sources.into_iter()
+            .map(|source| CodeSource {
+                name: source.name,
+                first_seen_at: DateTime::from_millis(source.first_seen_at),
+                last_seen_at: DateTime::from_millis(source.last_seen_at),
+                rewards: source.rewards,
+            })
+            .collect(),
+        active: row.get(5)?,
+        date: row.get::<_, Option<i64>>(6)?.map(DateTime::from_millis),
+        first_seen_at: row.get::<_, Option<i64>>(7)?.map(DateTime::from_millis),
+        last_seen_in_source_at: row.get::<_, Option<i64>>(8)?.map(DateTime::from_millis),
+        last_validated_at: row.get::<_, Option<i64>>(9)?.map(DateTime::from_millis),
+        deactivated_at: row.get::<_, Option<i64>>(10)?.map(DateTime::from_millis),
+        server,
+        regions: regions.into_iter()
+            .map(|region| RegionStatus {
+                region: region.region,
+                valid: region.valid,
+                checked_at: DateTime::from_millis(region.checked_at),
+            })
+            .collect(),
+    })
+}
+
+fn write_code(conn: &Connection, code: &RedemptionCode) -> Result<(), rusqlite::Error> {
+    let sources: Vec<StoredSource> = code.sources.iter()
+        .map(|source| StoredSource {
+            name: source.name.clone(),
+            first_seen_at: source.first_seen_at.timestamp_millis(),
+            last_seen_at: source.last_seen_at.timestamp_millis(),
+            rewards: source.rewards.clone(),
+        })
+        .collect();
+
+    conn.execute(
+        &format!("INSERT OR REPLACE INTO codes ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)", CODE_COLUMNS),
+        params![
+            code.code,
+            to_json(&code.rewards)?,
+            to_json(&code.items)?,
+            code.source,
+            to_json(&sources)?,
+            code.active,
+            millis(code.date),
+            millis(code.first_seen_at),
+            millis(code.last_seen_in_source_at),
+            millis(code.last_validated_at),
+            millis(code.deactivated_at),
+            code.server.as_str(),
+            stored_regions(&code.regions)?,
+        ],
+    )?;
+
+    Ok(())
+}
+
+fn stored_regions(regions: &[RegionStatus]) -> Result<String, rusqlite::Error> {
+    let regions: Vec<StoredRegion> = regions.iter()
+        .map(|region| StoredRegion {
+            region: region.region.clone(),
+            valid: region.valid,
+            checked_at: region.checked_at.timestamp_millis(),
+        })
+        .collect();
+    to_json(&regions)
+}
+
+fn news_from_row(row: &Row) -> Result<NewsItem, rusqlite::Error> {
+    let banner = match row.get::<_, Option<String>>(5)? {
+        Some(json) => Some(from_json(5, &json)?),
+        None => None,
+    };
+
+    Ok(NewsItem {
+        id: None,
+        external_id: row.get(0)?,
+        lang: row.get(1)?,
+        title: row.get(2)?,
+        description: row.get(3)?,
+        created_at: row.get(4)?,
+        banner,
+        url: row.get(6)?,
+        news_type: row.get(7)?,
+        start_at: row.get(8)?,
+        end_at: row.get(9)?,
+    })
+}
+
+#[async_trait]
+impl CodeRepository for SqliteStorage {
+    async fn save_codes(&self, codes: &[RedemptionCode]) -> StorageResult<bool> {
+        let codes = codes.to_vec();
+        let count = codes.len();
+
+        let changed = self.with_connection(move |conn| {
+            let tx = conn.transaction()?;
+            let mut changed = false;
+            for code in &codes {
+                let now = DateTime::now();
+                let existing = tx
+                    .query_row(
+                        &format!("SELECT {} FROM codes WHERE code = ?1", CODE_COLUMNS),
+                        params![code.code],
+                        code_from_row,
+                    )
+                    .optional()?;
+
+                let stored = match existing {
+                    Some(mut existing) => {
+                        changed |= merge_scraped_code(&mut existing, code, now);
+                        existing
+                    },
+                    None => {
+                        changed = true;
+                        new_stored_code(code, now)
+                    },
+                };
+                write_code(&tx, &stored)?;
+            }
+            tx.commit()?;
+            Ok(changed)
+        }).await?;
+
+        debug!("Saved {} codes to SQLite", count);
+        Ok(changed)
+    }
+
+    async fn get_codes(&self) -> StorageResult<(Vec<RedemptionCode>, Vec<RedemptionCode>)> {
+        let codes = self.with_connection(|conn| {
+            let mut statement = conn.prepare(&format!("SELECT {} FROM codes ORDER BY rowid", CODE_COLUMNS))?;
+            let codes = statement.query_map([], code_from_row)?.collect::<Result<Vec<_>, _>>();
+            codes
+        }).await?;
+
+        Ok(codes.into_iter().partition(|code| code.active))
+    }
+
+    async fn get_code(&self, code: &str) -> StorageResult<Option<RedemptionCode>> {
+        let code = code.to_string();
+        self.with_connection(move |conn| {
+            conn.query_row(
+                &format!("SELECT {} FROM codes WHERE code = ?1", CODE_COLUMNS),
+                params![code],
+                code_from_row,
+            ).optional()
+        }).await
+    }
+
+    async fn update_code_status(&self, code: &str, active: bool) -> StorageResult<()> {
+        let code_str = code.to_string();
+        let deactivated_at = if active { None } else { Some(DateTime::now().timestamp_millis()) };
+
+        self.with_connection(move |conn| {
+            conn.execute(
+                "UPDATE codes SET active = ?1, deactivated_at = ?2 WHERE code = ?3",
+                params![active, deactivated_at, code_str],
+            )
+        }).await?;
+
+        info!("Updated code {} status to active={}", code, active);
+        Ok(())
+    }
+
+    async fn mark_validated(&self, code: &str) -> StorageResult<()> {
+        let code = code.to_string();
+        let now = DateTime::now().timestamp_millis();
+
+        self.with_connection(move |conn| {
+            conn.execute("UPDATE codes SET last_validated_at = ?1 WHERE code = ?2", params![now, code])
+        }).await?;
+
+        Ok(())
+    }
+
+    async fn update_code_regions(&self, code: &str, regions: &[RegionStatus]) -> StorageResult<()> {
+        let code = code.to_string();
+        let regions = stored_regions(regions)?;
+
+        self.with_connection(move |conn| {
+            conn.execute("UPDATE codes SET regions = ?1 WHERE code = ?2", params![regions, code])
+        }).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NewsRepository for SqliteStorage {
+    async fn save_news(&self, news: &[NewsItem]) -> StorageResult<SavedNews> {
+        let news = news.to_vec();
+
+        self.with_connection(move |conn| {
+            let tx = conn.transaction()?;
+            let mut saved = SavedNews::default();
+            for item in news {
+                let existing = tx.query_row(
+                    &format!("SELECT {} FROM news WHERE id = ?1 AND lang = ?2", NEWS_COLUMNS),
+                    params![item.external_id, item.lang],
+                    news_from_row,
+                ).optional()?;
+                if existing.as_ref() == Some(&item) {
+                    continue;
+                }
+
+                let banner = item.banner.as_ref().map(to_json).transpose()?;
+
+                tx.execute(
+                    &format!("INSERT OR REPLACE INTO news ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)", NEWS_COLUMNS),
+                    params![
+                        item.external_id,
+                        item.lang,
+                        item.title,
+                        item.description,
+                        item.created_at,
+                        banner,
+                        item.url,
+                        item.news_type,
+                        item.start_at,
+                        item.end_at,
+                    ],
+                )?;
+
+                saved.changed = true;
+                if existing.is_none() {
+                    info!("New news item added: {} ({})", item.title, item.lang);
+                    saved.added.push(item);
+                }
+            }
+            tx.commit()?;
+            Ok(saved)
+        }).await
+    }
+
+    async fn get_news(&self, query: &NewsQuery<'_>) -> StorageResult<Vec<NewsItem>> {
+        let now = chrono::Utc::now().timestamp();
+        let mut sql = format!("SELECT {} FROM news WHERE lang = ?", NEWS_COLUMNS);
+        let mut values = vec![Value::from(query.lang.to_string())];
+
+        if let Some(news_type) = query.news_type {
+            sql.push_str(" AND type = ?");
+            values.push(Value::from(news_type.to_string()));
+        }
+
+        if let Some(status) = query.status {
+            match status {
+                EventStatus::Ongoing => {
+                    sql.push_str(" AND start_at <= ? AND end_at > ?");
+                    values.push(Value::from(now));
+                    values.push(Value::from(now));
+                },
+                EventStatus::Upcoming => {
+                    sql.push_str(" AND start_at > ?");
+                    values.push(Value::from(now));
+                },
+                EventStatus::Ended => {
+                    sql.push_str(" AND end_at <= ?");
+                    values.push(Value::from(now));
+                },
+            }
+        }
+
+        sql.push_str(" ORDER BY created_at DESC LIMIT ?");
+        values.push(Value::from(query.limit as i64));
+
+        self.with_connection(move |conn| {
+            let mut statement = conn.prepare(&sql)?;
+            let news = statement.query_map(params_from_iter(values), news_from_row)?.collect();
+            news
+        }).await
+    }
+}
+
+#[async_trait]
+impl DeliveryLogRepository for SqliteStorage {
+    async fn log_delivery(&self, delivery: &WebhookDelivery) -> StorageResult<()> {
+        let delivery = delivery.clone();
+
+        self.with_connection(move |conn| {
+            conn.execute(
+                "INSERT INTO webhook_deliveries (webhook, event, code, attempts, success, status, error, delivered_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    delivery.webhook,
+                    delivery.event,
+                    delivery.code,
+                    delivery.attempts,
+                    delivery.success,
+                    delivery.status,
+                    delivery.error,
+                    delivery.delivered_at.timestamp_millis(),
+                ],
+            )
+        }).await?;
+
+        Ok(())
+    }
+
+    async fn get_deliveries(&self, limit: usize) -> StorageResult<Vec<WebhookDelivery>> {
+        self.with_connection(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT webhook, event, code, attempts, success, status, error, delivered_at \
+                 FROM webhook_deliveries ORDER BY id DESC LIMIT ?1",
+            )?;
+            let deliveries = statement
+                .query_map(params![limit as i64], |row| {
+                    Ok(WebhookDelivery {
+                        webhook: row.get(0)?,
+                        event: row.get(1)?,
+                        code: row.get(2)?,
+                        attempts: row.get(3)?,
+                        success: row.get(4)?,
+                        status: row.get(5)?,
+                        error: row.get(6)?,
+                        delivered_at: DateTime::from_millis(row.get(7)?),
+                    })
+                })?
+                .collect();
+            deliveries
+        }).await
+    }
+}
+
+#[async_trait]
+impl ValidationLogRepository for SqliteStorage {
+    async fn log_validation(&self, attempt: &ValidationAttempt) -> StorageResult<()> {
+        let attempt = attempt.clone();
+
+        self.with_connection(move |conn| {
+            conn.execute(
+                "INSERT INTO validations (code, uid, region, retcode, message, result, validated_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    attempt.code,
+                    attempt.uid,
+                    attempt.region,
+                    attempt.retcode,
+                    attempt.message,
+                    attempt.result,
+                    attempt.validated_at.timestamp_millis(),
+                ],
+            )
+        }).await?;
+
+        Ok(())
+    }
+
+    async fn get_validations(&self, code: &str, limit: usize) -> StorageResult<Vec<ValidationAttempt>> {
+        let code = code.to_string();
+
+        self.with_connection(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT code, uid, region, retcode, message, result, validated_at \
+                 FROM validations WHERE code = ?1 ORDER BY id DESC LIMIT ?2",
+            )?;
+            let validations = statement
+                .query_map(params![code, limit as i64], |row| {
+                    Ok(ValidationAttempt {
+                        code: row.get(0)?,
+                        uid: row.get(1)?,
+                        region: row.get(2)?,
+                        retcode: row.get(3)?,
+                        message: row.get(4)?,
+                        result: row.get(5)?,
+                        validated_at: DateTime::from_millis(row.get(6)?),
+                    })
+                })?
+                .collect();
+            validations
+        }).await
+    }
+}
+
+fn queued_validation_from_row(row: &Row) -> Result<QueuedValidation, rusqlite::Error> {
+    let priority: String = row.get(1)?;
+    let priority = ValidationPriority::parse(&priority).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, format!("unknown priority {}", priority).into())
+    })?;
+
+    Ok(QueuedValidation {
+        code: row.get(0)?,
+        priority,
+        waiting_since: DateTime::from_millis(row.get(2)?),
+        not_before: DateTime::from_millis(row.get(3)?),
+        retries: row.get(4)?,
+    })
+}
+
+#[async_trait]
+impl ValidationQueueRepository for SqliteStorage {
+    async fn enqueue_validation(&self, entry: &QueuedValidation) -> StorageResult<bool> {
+        let entry = entry.clone();
+
+        let added = self.with_connection(move |conn| {
+            conn.execute(
+                "INSERT OR IGNORE INTO validation_queue (code, priority, waiting_since, not_before, retries) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    entry.code,
+                    entry.priority.as_str(),
+                    entry.waiting_since.timestamp_millis(),
+                    entry.not_before.timestamp_millis(),
+                    entry.retries,
+                ],
+            )
+        }).await?;
+
+        Ok(added > 0)
+    }
+
+    async fn update_queued_validation(&self, entry: &QueuedValidation) -> StorageResult<()> {
+        let entry = entry.clone();
+
+        self.with_connection(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO validation_queue (code, priority, waiting_since, not_before, retries) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    entry.code,
+                    entry.priority.as_str(),
+                    entry.waiting_since.timestamp_millis(),
+                    entry.not_before.timestamp_millis(),
+                    entry.retries,
+                ],
+            )
+        }).await?;
+
+        Ok(())
+    }
+
+    async fn remove_queued_validation(&self, code: &str) -> StorageResult<()> {
+        let code = code.to_string();
+        self.with_connection(move |conn| conn.execute("DELETE FROM validation_queue WHERE code = ?1", params![code])).await?;
+        Ok(())
+    }
+
+    async fn get_validation_queue(&self) -> StorageResult<Vec<QueuedValidation>> {
+        self.with_connection(|conn| {
+            let mut statement = conn.prepare(
+                "SELECT code, priority, waiting_since, not_before, retries FROM validation_queue",
+            )?;
+            let queue = statement.query_map([], queued_validation_from_row)?.collect();
+            queue
+        }).await
+    }
+}
+
+#[async_trait]
+impl HealthCheck for SqliteStorage {
+    async fn ping(&self) -> StorageResult<()> {
+        self.with_connection(|conn| conn.query_row("SELECT 1", [], |_| Ok(()))).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scraped_code(code: &str, source: &str) -> RedemptionCode {
+        let rewards = vec!["50 Stellar Jade".to_string()];
+        RedemptionCode {
+            id: None,
+            code: code.to_string(),
+            rewards: rewards.clone(),
+            items: crate::rewards::parse_rewards(&rewards),
+            source: source.to_string(),
+            sources: vec![CodeSource::new(source, &rewards, DateTime::now())],
+            active: true,
+            date: None,
+            first_seen_at: None,
+            last_seen_in_source_at: None,
+            last_validated_at: None,
+            deactivated_at: None,
+            server: Server::Global,
+            regions: Vec::new(),
+        }
+    }
+
+    fn event(id: &str, created_at: i64, start_at: i64, end_at: i64) -> NewsItem {
+        NewsItem {
+            id: None,
+            external_id: id.to_string(),
+            title: format!("Event {}", id),
+            description: String::new(),
+            created_at,
+            banner: Some(vec!["https://example.com/banner.png".to_string()]),
+            url: format!("https://www.hoyolab.com/article/{}", id),
+            news_type: "event".to_string(),
+            lang: "en-us".to_string(),
+            start_at: Some(start_at),
+            end_at: Some(end_at),
+        }
+    }
+
+    #[test]
+    fn test_migrations_are_idempotent() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        migrate(&mut conn).unwrap();
+
+        let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+    }
+
+    #[tokio::test]
+    async fn test_save_and_update_codes() {
+        let storage = SqliteStorage::open(":memory:").unwrap();
+        assert!(storage.save_codes(&[scraped_code("STARRAILGIFT", "Game8")]).await.unwrap());
+        assert!(storage.save_codes(&[scraped_code("STARRAILGIFT", "Prydwen"), scraped_code("VAJEGY4MNMDK", "Hoyolab")]).await.unwrap());
+        assert!(!storage.save_codes(&[scraped_code("VAJEGY4MNMDK", "Hoyolab")]).await.unwrap());
+        storage.update_code_status("VAJEGY4MNMDK", false).await.unwrap();
+
+        let (active, inactive) = storage.get_codes().await.unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(inactive.len(), 1);
+        assert!(inactive[0].deactivated_at.is_some());
+
+        let code = storage.get_code("STARRAILGIFT").await.unwrap().unwrap();
+        assert_eq!(code.sources.len(), 2);
+        assert_eq!(code.items[0].item_id.as_deref(), Some("stellar_jade"));
+        assert!(code.first_seen_at.is_some());
+        assert!(storage.get_code("MISSING").await.unwrap().is_none());
+
+        // Region statuses survive later scrapes
+        let usa = RegionStatus { region: "prod_official_usa".to_string(), valid: true, checked_at: DateTime::from_millis(1000) };
+        storage.update_code_regions("STARRAILGIFT", std::slice::from_ref(&usa)).await.unwrap();
+        storage.save_codes(&[scraped_code("STARRAILGIFT", "Game8")]).await.unwrap();
+        assert_eq!(storage.get_code("STARRAILGIFT").await.unwrap().unwrap().regions, vec![usa]);
+    }
+
+    #[tokio::test]
+    async fn test_save_and_filter_news() {
+        let storage = SqliteStorage::open(":memory:").unwrap();
+        let now = chrono::Utc::now().timestamp();
+        storage.save_news(&[
+            event("1", 100, now - 60, now + 60),
+            event("2", 200, now + 60, now + 120),
+        ]).await.unwrap();
+        // Saving again updates in place, and only counts as a change if something differs
+        let saved = storage.save_news(&[event("1", 100, now - 60, now + 60)]).await.unwrap();
+        assert!(saved.added.is_empty());
+        assert!(!saved.changed);
+        let saved = storage.save_news(&[event("1", 100, now - 60, now + 90)]).await.unwrap();
+        assert!(saved.added.is_empty());
+        assert!(saved.changed);
+
+        let query = NewsQuery { news_type: Some("event"), lang: "en-us", status: None, limit: 20 };
+        let events = storage.get_news(&query).await.unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].external_id, "2");
+        assert_eq!(events[0].banner.as_ref().map(Vec::len), Some(1));
+
+        let query = NewsQuery { news_type: Some("event"), lang: "en-us", status: Some(EventStatus::Ongoing), limit: 20 };
+        let ongoing = storage.get_news(&query).await.unwrap();
+        assert_eq!(ongoing.len(), 1);
+        assert_eq!(ongoing[0].external_id, "1");
+    }
+
+    #[tokio::test]
+    async fn test_delivery_log() {
+        let storage = SqliteStorage::open(":memory:").unwrap();
+        for (code, success) in [("STARRAILGIFT", true), ("VAJEGY4MNMDK", false)] {
+            storage.log_delivery(&WebhookDelivery {
+                webhook: "https://example.com/…#1a2b3c4d5e6f".to_string(),
+                event: "code.discovered".to_string(),
+                code: code.to_string(),
+                attempts: if success { 1 } else { 4 },
+                success,
+                status: Some(if success { 200 } else { 502 }),
+                error: (!success).then(|| "Webhook responded with 502 Bad Gateway".to_string()),
+                delivered_at: DateTime::now(),
+            }).await.unwrap();
+        }
+
+        let deliveries = storage.get_deliveries(10).await.unwrap();
+        assert_eq!(deliveries.len(), 2);
+        assert_eq!(deliveries[0].code, "VAJEGY4MNMDK");
+        assert_eq!(deliveries[0].attempts, 4);
+        assert!(!deliveries[0].success);
+        assert!(deliveries[1].error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_validation_log() {
+        let storage = SqliteStorage::open(":memory:").unwrap();
+        let attempts = [
+            ("STARRAILGIFT", Some(-2016), "Redemption in cooldown", "cooldown"),
+            ("VAJEGY4MNMDK", Some(0), "OK", "valid"),
+            ("STARRAILGIFT", Some(-2001), "Redemption code has expired", "expired"),
+            ("STARRAILGIFT", None, "HTTP 502 Bad Gateway", "unknown"),
+        ];
+        for (code, retcode, message, result) in attempts {
+            storage.log_validation(&ValidationAttempt {
+                code: code.to_string(),
+                uid: "800000001".to_string(),
+                region: "prod_official_usa".to_string(),
+                retcode,
+                message: message.to_string(),
+                result: result.to_string(),
+                validated_at: DateTime::now(),
+            }).await.unwrap();
+        }
+
+        let validations = storage.get_validations("STARRAILGIFT", 2).await.unwrap();
+        assert_eq!(validations.len(), 2);
+        assert_eq!(validations[0].retcode, None);
+        assert_eq!(validations[1].result, "expired");
+        assert!(storage.get_validations("MISSING", 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_validation_queue() {
+        let storage = SqliteStorage::open(":memory:").unwrap();
+        let entry = QueuedValidation::new("STARRAILGIFT", ValidationPriority::New, DateTime::from_millis(1000));
+        assert!(storage.enqueue_validation(&entry).await.unwrap());
+        // A code is only queued once
+        let stale = QueuedValidation::new("STARRAILGIFT", ValidationPriority::Revalidation, DateTime::from_millis(0));
+        assert!(!storage.enqueue_validation(&stale).await.unwrap());
+
+        let retried = QueuedValidation { retries: 2, not_before: DateTime::from_millis(5000), ..entry };
+        storage.update_queued_validation(&retried).await.unwrap();
+        assert_eq!(storage.get_validation_queue().await.unwrap(), vec![retried]);
+
+        storage.remove_queued_validation("STARRAILGIFT").await.unwrap();
+        assert!(storage.get_validation_queue().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,173 @@
+use mongodb::bson::DateTime;
+use serde::{Deserialize, Serialize};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+use crate::config::ValidatorConfig;
+
+static REDEEM_BUDGET: LazyLock<RequestBudget> = LazyLock::new(RequestBudget::default);
+static QUEUE_CHANGED: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// Why a code is waiting for validation. Lower priorities are validated first.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationPriority {
+    /// Discovered by the last scrapes and never validated.
+    New,
+    /// Active, but not validated for `validator.revalidate_after` seconds.
+    Revalidation,
+}
+
+impl ValidationPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValidationPriority::New => "new",
+            ValidationPriority::Revalidation => "revalidation",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "new" => Some(ValidationPriority::New),
+            "revalidation" => Some(ValidationPriority::Revalidation),
+            _ => None,
+        }
+    }
+}
+
+/// A code waiting in the validation queue. Each code is queued at most once.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QueuedValidation {
+    pub code: String,
+    pub priority: ValidationPriority,
+    /// Orders codes of the same priority, oldest first: when a new code was discovered, or
+    /// when an active code was last validated.
+    pub waiting_since: DateTime,
+    /// Not validated before this, after a cooldown or an inconclusive result.
+    pub not_before: DateTime,
+    /// Inconclusive validations in a row, doubling the delay before the next one.
+    pub retries: u32,
+}
+
+impl QueuedValidation {
+    pub fn new(code: &str, priority: ValidationPriority, waiting_since: DateTime) -> Self {
+        Self {
+            code: code.to_string(),
+            priority,
+            waiting_since,
+            not_before: waiting_since,
+            retries: 0,
+        }
+    }
+
+    /// The entry to keep after an inconclusive validation at `now`, retried with backoff.
+    pub fn retry(&self, config: &ValidatorConfig, now: DateTime) -> Self {
+        let delay = config.retry_delay.saturating_mul(1 << self.retries.min(16)).min(config.max_retry_delay);
+        Self {
+            not_before: DateTime::from_millis(now.timestamp_millis() + delay as i64 * 1000),
+            retries: self.retries + 1,
+            ..self.clone()
+        }
+    }
+}
+
+/// The most urgent entry of `queue` that may be validated at `now`.
+pub fn next_ready(queue: &[QueuedValidation], now: DateTime) -> Option<&QueuedValidation> {
+    queue.iter()
+        .filter(|entry| entry.not_before <= now)
+        .min_by_key(|entry| (entry.priority, entry.waiting_since))
+}
+
+/// Paces redeem requests to a number per minute across every `ValidatorService`, so that
+/// adding accounts or running validations side by side does not raise the request rate.
+#[derive(Default)]
+pub struct RequestBudget {
+    next_slot: Mutex<Option<Instant>>,
+}
+
+pub fn budget() -> &'static RequestBudget {
+    &REDEEM_BUDGET
+}
+
+impl RequestBudget {
+    /// Waits for the next free slot at `requests_per_minute`, or returns straight away if it is 0.
+    pub async fn acquire(&self, requests_per_minute: u32) {
+        if requests_per_minute == 0 {
+            return;
+        }
+
+        let interval = Duration::from_secs(60) / requests_per_minute;
+        let slot = {
+            let mut next_slot = self.next_slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let now = Instant::now();
+            let slot = next_slot.map_or(now, |next| next.max(now));
+            *next_slot = Some(slot + interval);
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+/// Wakes the validation worker, e.g. after new codes were queued.
+pub fn wake() {
+    QUEUE_CHANGED.notify_one();
+}
+
+/// Waits until the queue changes or `timeout` passes, whichever comes first.
+pub async fn wait(timeout: Duration) {
+    let _ = tokio::time::timeout(timeout, QUEUE_CHANGED.notified()).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: i64) -> DateTime {
+        DateTime::from_millis(seconds * 1000)
+    }
+
+    #[test]
+    fn test_next_ready_orders_by_priority_then_age() {
+        let mut cooling = QueuedValidation::new("COOLINGDOWN1", ValidationPriority::New, at(0));
+        cooling.not_before = at(500);
+        let queue = vec![
+            QueuedValidation::new("STALECODE001", ValidationPriority::Revalidation, at(10)),
+            QueuedValidation::new("STALECODE002", ValidationPriority::Revalidation, at(5)),
+            QueuedValidation::new("NEWCODE00001", ValidationPriority::New, at(100)),
+            cooling,
+        ];
+
+        assert_eq!(next_ready(&queue, at(200)).unwrap().code, "NEWCODE00001");
+        assert_eq!(next_ready(&queue, at(500)).unwrap().code, "COOLINGDOWN1");
+        assert_eq!(next_ready(&queue[..2], at(200)).unwrap().code, "STALECODE002");
+        assert!(next_ready(&queue[3..], at(200)).is_none());
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let config = ValidatorConfig { retry_delay: 60, max_retry_delay: 200, ..Default::default() };
+        let entry = QueuedValidation::new("STARRAILGIFT", ValidationPriority::New, at(0));
+
+        let first = entry.retry(&config, at(1000));
+        assert_eq!((first.not_before, first.retries), (at(1060), 1));
+        let second = first.retry(&config, at(1060));
+        assert_eq!(second.not_before, at(1180));
+        let third = second.retry(&config, at(1180));
+        assert_eq!(third.not_before, at(1380));
+        assert_eq!(third.waiting_since, at(0));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_budget_spaces_requests() {
+        let budget = RequestBudget::default();
+        let started = Instant::now();
+        for _ in 0..3 {
+            budget.acquire(6).await;
+        }
+        assert_eq!(started.elapsed(), Duration::from_secs(20));
+
+        budget.acquire(0).await;
+        assert_eq!(started.elapsed(), Duration::from_secs(20));
+    }
+}
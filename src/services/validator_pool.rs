@@ -0,0 +1,169 @@
+use log::{error, info};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, LazyLock, Mutex};
+use crate::config::AccountSelection;
+
+static VALIDATOR_POOL: LazyLock<Arc<AccountPool>> = LazyLock::new(Arc::default);
+
+/// Redeem history of one validator account, keyed by UID. Timestamps are Unix seconds.
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct AccountStatus {
+    pub uid: String,
+    pub last_used_at: Option<i64>,
+    /// Set after a `-2016` cooldown response; the account is skipped until then.
+    pub cooldown_until: Option<i64>,
+    /// Set once the redeem endpoint rejects the account's credentials. Cleared by a restart.
+    pub quarantined: bool,
+    pub last_error: Option<String>,
+}
+
+impl AccountStatus {
+    fn is_usable(&self, now: i64) -> bool {
+        !self.quarantined && self.cooldown_until.is_none_or(|until| now >= until)
+    }
+}
+
+#[derive(Default)]
+struct PoolState {
+    accounts: BTreeMap<String, AccountStatus>,
+    /// Round-robin position for each list of UIDs passed to `select`, such as the accounts
+    /// of one region, as an index into that list.
+    cursors: HashMap<Vec<String>, usize>,
+}
+
+/// Shares code validation between the configured Hoyolab accounts, skipping accounts that
+/// are cooling down and quarantining accounts whose credentials stopped working. State
+/// outlives the `ValidatorService`, which is rebuilt for every scheduler run.
+#[derive(Default)]
+pub struct AccountPool {
+    state: Mutex<PoolState>,
+}
+
+pub fn instance() -> Arc<AccountPool> {
+    VALIDATOR_POOL.clone()
+}
+
+impl AccountPool {
+    fn lock(&self) -> std::sync::MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Picks one of `uids` for the next redeem request and marks it as used. Returns its index,
+    /// or `None` if every account is cooling down or quarantined.
+    pub fn select(&self, uids: &[String], selection: AccountSelection) -> Option<usize> {
+        self.select_at(uids, selection, chrono::Utc::now().timestamp())
+    }
+
+    fn select_at(&self, uids: &[String], selection: AccountSelection, now: i64) -> Option<usize> {
+        let mut state = self.lock();
+        for uid in uids {
+            state.accounts.entry(uid.clone()).or_insert_with(|| AccountStatus { uid: uid.clone(), ..Default::default() });
+        }
+
+        let usable = |state: &PoolState, index: usize| state.accounts[&uids[index]].is_usable(now);
+        let index = match selection {
+            AccountSelection::RoundRobin => {
+                let start = state.cursors.get(uids).copied().unwrap_or(0);
+                (0..uids.len())
+                    .map(|offset| (start + offset) % uids.len())
+                    .find(|&index| usable(&state, index))
+            },
+            AccountSelection::LeastRecentlyUsed => (0..uids.len())
+                .filter(|&index| usable(&state, index))
+                .min_by_key(|&index| state.accounts[&uids[index]].last_used_at),
+        }?;
+
+        state.cursors.insert(uids.to_vec(), (index + 1) % uids.len());
+        if let Some(account) = state.accounts.get_mut(&uids[index]) {
+            account.last_used_at = Some(now);
+        }
+        Some(index)
+    }
+
+    pub fn is_quarantined(&self, uid: &str) -> bool {
+        self.lock().accounts.get(uid).is_some_and(|account| account.quarantined)
+    }
+
+    /// Rests `uid` for `seconds` after the redeem endpoint reported a cooldown.
+    pub fn cool_down(&self, uid: &str, seconds: u64) {
+        self.cool_down_at(uid, seconds, chrono::Utc::now().timestamp());
+    }
+
+    fn cool_down_at(&self, uid: &str, seconds: u64, now: i64) {
+        let mut state = self.lock();
+        let account = state.accounts.entry(uid.to_string()).or_insert_with(|| AccountStatus { uid: uid.to_string(), ..Default::default() });
+        account.cooldown_until = Some(now + seconds as i64);
+        account.last_error = Some("Redemption cooldown".to_string());
+        info!("Validator account {} is cooling down for {}s", uid, seconds);
+    }
+
+    /// Stops using `uid` until the server restarts.
+    pub fn quarantine(&self, uid: &str, reason: &str) {
+        let mut state = self.lock();
+        let account = state.accounts.entry(uid.to_string()).or_insert_with(|| AccountStatus { uid: uid.to_string(), ..Default::default() });
+        if !account.quarantined {
+            error!("Quarantining validator account {}: {}", uid, reason);
+        }
+        account.quarantined = true;
+        account.last_error = Some(reason.to_string());
+    }
+
+    pub fn snapshot(&self) -> Vec<AccountStatus> {
+        self.lock().accounts.values().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uids() -> Vec<String> {
+        vec!["800000001".to_string(), "800000002".to_string(), "800000003".to_string()]
+    }
+
+    #[test]
+    fn test_round_robin_skips_unusable_accounts() {
+        let pool = AccountPool::default();
+        let uids = uids();
+        let picks: Vec<usize> = (0..4).filter_map(|_| pool.select_at(&uids, AccountSelection::RoundRobin, 0)).collect();
+        assert_eq!(picks, vec![0, 1, 2, 0]);
+
+        pool.cool_down_at("800000002", 60, 0);
+        pool.quarantine("800000003", "Invalid account credentials");
+        assert_eq!(pool.select_at(&uids, AccountSelection::RoundRobin, 10), Some(0));
+        assert_eq!(pool.select_at(&uids, AccountSelection::RoundRobin, 10), Some(0));
+
+        // Back in rotation once the cooldown ends; quarantine lasts
+        assert_eq!(pool.select_at(&uids, AccountSelection::RoundRobin, 60), Some(1));
+        assert_eq!(pool.select_at(&uids, AccountSelection::RoundRobin, 60), Some(0));
+
+        pool.quarantine("800000001", "Invalid account credentials");
+        pool.cool_down_at("800000002", 60, 60);
+        assert_eq!(pool.select_at(&uids, AccountSelection::RoundRobin, 61), None);
+    }
+
+    #[test]
+    fn test_least_recently_used() {
+        let pool = AccountPool::default();
+        let uids = uids();
+        assert_eq!(pool.select_at(&uids, AccountSelection::LeastRecentlyUsed, 0), Some(0));
+        assert_eq!(pool.select_at(&uids, AccountSelection::LeastRecentlyUsed, 1), Some(1));
+        assert_eq!(pool.select_at(&uids, AccountSelection::LeastRecentlyUsed, 2), Some(2));
+
+        pool.cool_down_at("800000001", 10, 3);
+        assert_eq!(pool.select_at(&uids, AccountSelection::LeastRecentlyUsed, 3), Some(1));
+        assert_eq!(pool.select_at(&uids, AccountSelection::LeastRecentlyUsed, 13), Some(0));
+    }
+
+    #[test]
+    fn test_round_robin_per_region() {
+        let pool = AccountPool::default();
+        let usa = uids();
+        let asia = vec!["810000001".to_string(), "810000002".to_string()];
+        let picks: Vec<(usize, usize)> = (0..3)
+            .filter_map(|_| Some((pool.select_at(&usa, AccountSelection::RoundRobin, 0)?, pool.select_at(&asia, AccountSelection::RoundRobin, 0)?)))
+            .collect();
+        assert_eq!(picks, vec![(0, 0), (1, 1), (2, 0)]);
+    }
+}
@@ -1,8 +1,14 @@
-use crate::config::Settings;
-use crate::resolvers::RedemptionCode;
+use crate::config::{HoyolabConfig, Settings};
+use crate::resolvers::{RedemptionCode, Server};
 use log::{info, error, warn};
-use serde::Deserialize;
-use reqwest::Client;
+use mongodb::bson::DateTime;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::utils::http::HttpClient;
+use super::metrics;
+use super::storage::Storage;
+use super::validation_queue;
+use super::validator_pool::{self, AccountPool};
 
 #[derive(Debug, Deserialize)]
 struct HoyolabResponse {
@@ -22,45 +28,228 @@ pub enum ValidationResult {
     Unknown(i32, String),
 }
 
+impl ValidationResult {
+    /// Metric label for the result.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ValidationResult::Valid => "valid",
+            ValidationResult::AlreadyRedeemed => "already_redeemed",
+            ValidationResult::Expired => "expired",
+            ValidationResult::Invalid => "invalid",
+            ValidationResult::Cooldown => "cooldown",
+            ValidationResult::InvalidCredentials => "invalid_credentials",
+            ValidationResult::MaxUsageReached => "max_usage_reached",
+            ValidationResult::Unknown(..) => "unknown",
+        }
+    }
+
+    /// Whether the code works, or `None` if the result says nothing about the code.
+    pub fn works(&self) -> Option<bool> {
+        match self {
+            ValidationResult::Valid | ValidationResult::AlreadyRedeemed => Some(true),
+            ValidationResult::Expired | ValidationResult::Invalid | ValidationResult::MaxUsageReached => Some(false),
+            ValidationResult::Cooldown | ValidationResult::InvalidCredentials | ValidationResult::Unknown(..) => None,
+        }
+    }
+}
+
+/// Result of validating a code with the accounts of one region.
+#[derive(Debug)]
+pub struct RegionValidation {
+    pub region: String,
+    pub result: Result<ValidationResult, Box<dyn std::error::Error + Send + Sync>>,
+}
+
+/// One redeem request made while validating a code, kept as the code's validation history.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ValidationAttempt {
+    pub code: String,
+    /// UID of the account that redeemed the code.
+    pub uid: String,
+    /// Region of the account. Empty for attempts recorded before per-region validation.
+    #[serde(default)]
+    pub region: String,
+    /// Missing when the request failed or the endpoint answered with an HTTP error.
+    pub retcode: Option<i32>,
+    pub message: String,
+    /// `ValidationResult::label` of the mapped result, or `error` if the request failed.
+    pub result: String,
+    pub validated_at: DateTime,
+}
+
+/// Answer of the redeem endpoint to one request.
+struct Redemption {
+    result: ValidationResult,
+    retcode: Option<i32>,
+    message: String,
+}
+
+pub const DEFAULT_REDEEM_URL: &str = "https://sg-hkrpg-api.hoyoverse.com/common/apicdkey/api/webExchangeCdkey";
+pub const DEFAULT_CN_REDEEM_URL: &str = "https://hkrpg-api.mihoyo.com/common/apicdkey/api/webExchangeCdkey";
+
 pub struct ValidatorService {
-    client: Client,
+    client: HttpClient,
+    /// For the China-server endpoint, which often needs its own proxy.
+    cn_client: HttpClient,
     config: Settings,
+    /// `hoyolab` followed by `validator.accounts`, one per UID.
+    accounts: Vec<HoyolabConfig>,
+    storage: Arc<dyn Storage>,
+    pool: Arc<AccountPool>,
 }
 
 impl ValidatorService {
-    pub fn new() -> Self {
-        let client = Client::new();
-        let config = Settings::new().expect("Failed to load configuration");
-        Self { client, config }
+    pub fn new(config: Settings, storage: Arc<dyn Storage>) -> Self {
+        let client = config.http.client("redeem");
+        let cn_client = config.http.client("redeem_cn");
+        let mut accounts: Vec<HoyolabConfig> = Vec::new();
+        for account in std::iter::once(&config.hoyolab).chain(&config.validator.accounts) {
+            if !accounts.iter().any(|existing| existing.uid == account.uid) {
+                accounts.push(account.clone());
+            }
+        }
+
+        Self { client, cn_client, config, accounts, storage, pool: validator_pool::instance() }
     }
 
-    pub async fn validate_code(&self, code: &RedemptionCode) -> Result<ValidationResult, Box<dyn std::error::Error + Send + Sync>> {
-        let url = "https://sg-hkrpg-api.hoyoverse.com/common/apicdkey/api/webExchangeCdkey";
-        
+    /// Uses `pool` instead of the shared account pool.
+    pub fn with_pool(mut self, pool: Arc<AccountPool>) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    /// Whether any account can validate codes of `server`.
+    pub fn supports(&self, server: Server) -> bool {
+        self.accounts.iter().any(|account| account.server == server)
+    }
+
+    /// Regions of `server` that have an account, in the order the accounts are configured.
+    pub fn regions(&self, server: Server) -> Vec<&str> {
+        let mut regions: Vec<&str> = Vec::new();
+        for account in self.accounts.iter().filter(|account| account.server == server) {
+            if !regions.contains(&account.region.as_str()) {
+                regions.push(&account.region);
+            }
+        }
+        regions
+    }
+
+    /// Validates `code` once in every region of its server, since some codes only work in
+    /// some regions.
+    pub async fn validate_code(&self, code: &RedemptionCode) -> Vec<RegionValidation> {
+        let mut validations = Vec::new();
+        for region in self.regions(code.server) {
+            let result = self.redeem(code, region).await;
+            let label = match &result {
+                Ok(result) => result.label(),
+                Err(_) => "error",
+            };
+            metrics::instance().validations.with_label_values(&[label]).inc();
+            validations.push(RegionValidation { region: region.to_string(), result });
+        }
+        validations
+    }
+
+    /// Redeems `code` with the next account of `region` from the pool, moving on to another
+    /// account when one is cooling down, has had its credentials rejected or fails to redeem.
+    /// Errors only when every account of the region failed.
+    async fn redeem(&self, code: &RedemptionCode, region: &str) -> Result<ValidationResult, Box<dyn std::error::Error + Send + Sync>> {
+        let pool = &self.pool;
+        let validator = &self.config.validator;
+        let mut accounts: Vec<&HoyolabConfig> = self.accounts.iter()
+            .filter(|account| account.server == code.server && account.region == region)
+            .collect();
+        let uids: Vec<String> = accounts.iter().map(|account| account.uid.clone()).collect();
+        let mut last_error = None;
+
+        loop {
+            let remaining: Vec<String> = accounts.iter().map(|account| account.uid.clone()).collect();
+            let Some(index) = pool.select(&remaining, validator.selection) else { break };
+            let account = accounts[index];
+            match self.redeem_with(account, code).await {
+                Ok(ValidationResult::Cooldown) => pool.cool_down(&account.uid, validator.cooldown),
+                Ok(ValidationResult::InvalidCredentials) => pool.quarantine(&account.uid, "Invalid account credentials"),
+                Ok(result) => return Ok(result),
+                // Another account may still get through; this one sits out the rest of the code
+                Err(e) => {
+                    error!("Failed to redeem code {} with account {}: {}", code.code, account.uid, e);
+                    accounts.remove(index);
+                    last_error = Some(e);
+                },
+            }
+        }
+
+        if accounts.is_empty() {
+            if let Some(e) = last_error {
+                return Err(e);
+            }
+        }
+        if uids.iter().all(|uid| pool.is_quarantined(uid)) {
+            error!("Every {} validator account is quarantined", region);
+            return Ok(ValidationResult::InvalidCredentials);
+        }
+        warn!("Every {} validator account is cooling down, skipping code {}", region, code.code);
+        Ok(ValidationResult::Cooldown)
+    }
+
+    /// Redeems `code` with `account` and records the attempt in the validation history.
+    async fn redeem_with(&self, account: &HoyolabConfig, code: &RedemptionCode) -> Result<ValidationResult, Box<dyn std::error::Error + Send + Sync>> {
+        let redemption = self.send_redeem(account, code).await;
+        let (retcode, message, result) = match &redemption {
+            Ok(redemption) => (redemption.retcode, redemption.message.clone(), redemption.result.label()),
+            Err(e) => (None, e.to_string(), "error"),
+        };
+        let attempt = ValidationAttempt {
+            code: code.code.clone(),
+            uid: account.uid.clone(),
+            region: account.region.clone(),
+            retcode,
+            message,
+            result: result.to_string(),
+            validated_at: DateTime::now(),
+        };
+        if let Err(e) = self.storage.log_validation(&attempt).await {
+            error!("Failed to record validation of code {}: {}", code.code, e);
+        }
+
+        redemption.map(|redemption| redemption.result)
+    }
+
+    async fn send_redeem(&self, account: &HoyolabConfig, code: &RedemptionCode) -> Result<Redemption, Box<dyn std::error::Error + Send + Sync>> {
+        let (client, url, game_biz, lang) = match account.server {
+            Server::Global => (&self.client, &self.config.upstream.redeem, "hkrpg_global", "en"),
+            Server::Cn => (&self.cn_client, &self.config.upstream.redeem_cn, "hkrpg_cn", "zh-cn"),
+        };
+
         let timestamp = chrono::Utc::now().timestamp_millis();
-        let game_biz = String::from("hkrpg_global");
-        let lang = String::from("en");
         
-        let response = self.client
+        let mut request = client
             .get(url)
-            .header("User-Agent", &self.config.hoyolab.user_agent)
-            .header("Cookie", &self.config.hoyolab.cookie)
+            .header("Cookie", &account.cookie)
             .query(&[
-                ("cdkey", &code.code),
-                ("game_biz", &game_biz),
-                ("lang", &lang),
-                ("region", &self.config.hoyolab.region),
+                ("cdkey", code.code.as_str()),
+                ("game_biz", game_biz),
+                ("lang", lang),
+                ("region", account.region.as_str()),
                 ("t", &timestamp.to_string()),
-                ("uid", &self.config.hoyolab.uid),
-            ])
-            .send()
-            .await?;
+                ("uid", account.uid.as_str()),
+            ]);
+        // The account's own browser, if configured, rather than the shared user agent
+        if !account.user_agent.is_empty() {
+            request = request.header("User-Agent", &account.user_agent);
+        }
+        validation_queue::budget().acquire(self.config.validator.requests_per_minute).await;
+        let response = client.send(request).await?;
 
         let status = response.status();
         
         if !status.is_success() {
             error!("Failed HTTP request for code {}: Status {}", code.code, status);
-            return Ok(ValidationResult::Unknown(status.as_u16() as i32, "HTTP request failed".to_string()));
+            return Ok(Redemption {
+                result: ValidationResult::Unknown(status.as_u16() as i32, "HTTP request failed".to_string()),
+                retcode: None,
+                message: format!("HTTP {}", status),
+            });
         }
 
         let response_body: HoyolabResponse = response.json().await?;
@@ -80,7 +269,7 @@ impl ValidatorService {
                 ValidationResult::Invalid
             },
             -2016 => {
-                warn!("Code {} is in cooldown", code.code);
+                warn!("Account {} is in cooldown", account.uid);
                 ValidationResult::Cooldown
             },
             -2006 => {
@@ -88,17 +277,206 @@ impl ValidatorService {
                 ValidationResult::MaxUsageReached
             },
             -1071 => {
-                error!("Invalid account credentials");
+                error!("Invalid credentials for account {}", account.uid);
                 ValidationResult::InvalidCredentials
             },
             _ => {
                 error!("Unknown response code {} for code {}: {}", 
                     response_body.retcode, code.code, response_body.message);
-                ValidationResult::Unknown(response_body.retcode, response_body.message)
+                ValidationResult::Unknown(response_body.retcode, response_body.message.clone())
             }
         };
 
-        info!("Validation result for code {}: {:?}", code.code, result);
-        Ok(result)
+        info!("Validation result for code {} with account {}: {:?}", code.code, account.uid, result);
+        Ok(Redemption {
+            result,
+            retcode: Some(response_body.retcode),
+            message: response_body.message,
+        })
+    }
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AccountSelection, UpstreamConfig, ValidatorConfig};
+    use crate::services::storage::ValidationLogRepository;
+    use crate::services::storage::memory::MemoryStorage;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use wiremock::matchers::{method, path, query_param};
+
+    fn account(uid: &str) -> HoyolabConfig {
+        regional_account(uid, "prod_official_usa")
+    }
+
+    fn regional_account(uid: &str, region: &str) -> HoyolabConfig {
+        HoyolabConfig {
+            region: region.to_string(),
+            uid: uid.to_string(),
+            cookie: format!("ltoken_v2={}", uid),
+            user_agent: String::new(),
+            server: Server::Global,
+        }
+    }
+
+    fn code(code: &str) -> RedemptionCode {
+        RedemptionCode {
+            id: None,
+            code: code.to_string(),
+            rewards: Vec::new(),
+            items: Vec::new(),
+            source: "Game8".to_string(),
+            sources: Vec::new(),
+            active: true,
+            date: None,
+            first_seen_at: None,
+            last_seen_in_source_at: None,
+            last_validated_at: None,
+            deactivated_at: None,
+            server: Server::Global,
+            regions: Vec::new(),
+        }
+    }
+
+    async fn mount_account(server: &MockServer, uid: &str, retcode: i32) {
+        Mock::given(method("GET"))
+            .and(path("/redeem"))
+            .and(query_param("uid", uid))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "retcode": retcode,
+                "message": "stub",
+            })))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_moves_on_from_unusable_accounts() {
+        let server = MockServer::start().await;
+        mount_account(&server, "810000001", -1071).await;
+        mount_account(&server, "810000002", -2016).await;
+        mount_account(&server, "810000003", 0).await;
+
+        let pool = Arc::new(AccountPool::default());
+        let storage = Arc::new(MemoryStorage::new());
+        let validator = ValidatorService::new(Settings {
+            hoyolab: account("810000001"),
+            validator: ValidatorConfig {
+                accounts: vec![account("810000002"), account("810000003"), account("810000001")],
+                selection: AccountSelection::LeastRecentlyUsed,
+                requests_per_minute: 0,
+                ..Default::default()
+            },
+            upstream: UpstreamConfig {
+                redeem: format!("{}/redeem", server.uri()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }, storage.clone()).with_pool(pool.clone());
+        assert_eq!(validator.accounts.len(), 3);
+
+        let validations = validator.validate_code(&code("STARRAILGIFT")).await;
+        assert_eq!(validations.len(), 1);
+        assert!(matches!(validations[0].result, Ok(ValidationResult::Valid)));
+
+        assert!(pool.is_quarantined("810000001"));
+        assert!(!pool.is_quarantined("810000002"));
+
+        // Only the working account is asked from now on
+        validator.validate_code(&code("VAJEGY4MNMDK")).await;
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 4);
+        assert!(requests[3].url.query().unwrap().contains("uid=810000003"));
+
+        // Every request is kept in the code's history, newest first
+        let history = storage.get_validations("STARRAILGIFT", 10).await.unwrap();
+        let results: Vec<&str> = history.iter().map(|attempt| attempt.result.as_str()).collect();
+        assert_eq!(results, vec!["valid", "cooldown", "invalid_credentials"]);
+        assert_eq!(history[1].uid, "810000002");
+        assert_eq!(history[1].retcode, Some(-2016));
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_validates_each_region() {
+        let server = MockServer::start().await;
+        mount_account(&server, "810000004", 0).await;
+        mount_account(&server, "810000005", -2003).await;
+        mount_account(&server, "810000006", 0).await;
+
+        let storage = Arc::new(MemoryStorage::new());
+        let validator = ValidatorService::new(Settings {
+            hoyolab: account("810000004"),
+            validator: ValidatorConfig {
+                accounts: vec![
+                    regional_account("810000005", "prod_official_asia"),
+                    regional_account("810000006", "prod_official_usa"),
+                ],
+                selection: AccountSelection::LeastRecentlyUsed,
+                requests_per_minute: 0,
+                ..Default::default()
+            },
+            upstream: UpstreamConfig {
+                redeem: format!("{}/redeem", server.uri()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }, storage.clone()).with_pool(Arc::default());
+        assert_eq!(validator.regions(Server::Global), vec!["prod_official_usa", "prod_official_asia"]);
+        assert!(validator.regions(Server::Cn).is_empty());
+
+        // One request per region, however many accounts a region has
+        let validations = validator.validate_code(&code("USAONLYCODE1")).await;
+        let results: Vec<(&str, Option<bool>)> = validations.iter()
+            .map(|validation| (validation.region.as_str(), validation.result.as_ref().unwrap().works()))
+            .collect();
+        assert_eq!(results, vec![("prod_official_usa", Some(true)), ("prod_official_asia", Some(false))]);
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+
+        let history = storage.get_validations("USAONLYCODE1", 10).await.unwrap();
+        assert_eq!(history[0].region, "prod_official_asia");
+        assert_eq!(history[0].uid, "810000005");
+    }
+
+    #[tokio::test]
+    async fn test_moves_on_from_failing_accounts() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/redeem"))
+            .and(query_param("uid", "810000007"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<html>"))
+            .mount(&server)
+            .await;
+        mount_account(&server, "810000008", 0).await;
+
+        let validator = ValidatorService::new(Settings {
+            hoyolab: account("810000007"),
+            validator: ValidatorConfig {
+                accounts: vec![account("810000008")],
+                requests_per_minute: 0,
+                ..Default::default()
+            },
+            upstream: UpstreamConfig {
+                redeem: format!("{}/redeem", server.uri()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }, Arc::new(MemoryStorage::new())).with_pool(Arc::default());
+
+        // One failing account does not decide the region
+        let validations = validator.validate_code(&code("STARRAILGIFT")).await;
+        assert!(matches!(validations[0].result, Ok(ValidationResult::Valid)));
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+
+        // Only an error from every account does
+        let validator = ValidatorService::new(Settings {
+            hoyolab: account("810000007"),
+            validator: ValidatorConfig { requests_per_minute: 0, ..Default::default() },
+            upstream: UpstreamConfig {
+                redeem: format!("{}/redeem", server.uri()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }, Arc::new(MemoryStorage::new())).with_pool(Arc::default());
+        assert!(validator.validate_code(&code("STARRAILGIFT")).await[0].result.is_err());
+    }
+}
@@ -0,0 +1,155 @@
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+
+/// A feed that can be rendered as either RSS 2.0 or Atom. Timestamps are Unix seconds.
+pub struct Feed {
+    pub title: String,
+    pub description: String,
+    pub link: String,
+    pub self_url: String,
+    pub lang: String,
+    pub entries: Vec<FeedEntry>,
+}
+
+pub struct FeedEntry {
+    /// Stable identifier, used as the RSS `guid` and Atom `id`.
+    pub id: String,
+    pub title: String,
+    pub link: String,
+    pub summary: String,
+    pub published: i64,
+    pub updated: i64,
+}
+
+impl Feed {
+    /// The most recent entry update, or now for an empty feed.
+    pub fn updated(&self) -> i64 {
+        self.entries.iter()
+            .map(|entry| entry.updated)
+            .max()
+            .unwrap_or_else(|| Utc::now().timestamp())
+    }
+
+    pub fn to_rss(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str("<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\">\n<channel>\n");
+        let _ = writeln!(xml, "<title>{}</title>", escape(&self.title));
+        let _ = writeln!(xml, "<link>{}</link>", escape(&self.link));
+        let _ = writeln!(xml, "<description>{}</description>", escape(&self.description));
+        let _ = writeln!(xml, "<language>{}</language>", escape(&self.lang));
+        let _ = writeln!(xml, "<lastBuildDate>{}</lastBuildDate>", rfc2822(self.updated()));
+        let _ = writeln!(xml, "<atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\"/>", escape(&self.self_url));
+
+        for entry in &self.entries {
+            xml.push_str("<item>\n");
+            let _ = writeln!(xml, "<title>{}</title>", escape(&entry.title));
+            let _ = writeln!(xml, "<link>{}</link>", escape(&entry.link));
+            let _ = writeln!(xml, "<guid isPermaLink=\"false\">{}</guid>", escape(&entry.id));
+            let _ = writeln!(xml, "<description>{}</description>", escape(&entry.summary));
+            let _ = writeln!(xml, "<pubDate>{}</pubDate>", rfc2822(entry.published));
+            xml.push_str("</item>\n");
+        }
+
+        xml.push_str("</channel>\n</rss>\n");
+        xml
+    }
+
+    pub fn to_atom(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(xml, "<feed xmlns=\"http://www.w3.org/2005/Atom\" xml:lang=\"{}\">", escape(&self.lang));
+        let _ = writeln!(xml, "<id>{}</id>", escape(&self.self_url));
+        let _ = writeln!(xml, "<title>{}</title>", escape(&self.title));
+        let _ = writeln!(xml, "<subtitle>{}</subtitle>", escape(&self.description));
+        let _ = writeln!(xml, "<updated>{}</updated>", rfc3339(self.updated()));
+        let _ = writeln!(xml, "<link href=\"{}\"/>", escape(&self.link));
+        let _ = writeln!(xml, "<link rel=\"self\" href=\"{}\"/>", escape(&self.self_url));
+        let _ = writeln!(xml, "<author><name>{}</name></author>", escape(&self.title));
+
+        for entry in &self.entries {
+            xml.push_str("<entry>\n");
+            let _ = writeln!(xml, "<id>{}</id>", escape(&entry.id));
+            let _ = writeln!(xml, "<title>{}</title>", escape(&entry.title));
+            let _ = writeln!(xml, "<link href=\"{}\"/>", escape(&entry.link));
+            let _ = writeln!(xml, "<published>{}</published>", rfc3339(entry.published));
+            let _ = writeln!(xml, "<updated>{}</updated>", rfc3339(entry.updated));
+            let _ = writeln!(xml, "<summary>{}</summary>", escape(&entry.summary));
+            xml.push_str("</entry>\n");
+        }
+
+        xml.push_str("</feed>\n");
+        xml
+    }
+}
+
+fn timestamp(seconds: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(seconds, 0).unwrap_or_default()
+}
+
+fn rfc2822(seconds: i64) -> String {
+    timestamp(seconds).to_rfc2822()
+}
+
+fn rfc3339(seconds: i64) -> String {
+    timestamp(seconds).to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {},
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed() -> Feed {
+        Feed {
+            title: "Honkai: Star Rail Codes".to_string(),
+            description: "Redemption codes & rewards".to_string(),
+            link: "https://hsr.hoyoverse.com/gift".to_string(),
+            self_url: "https://api.ennead.cc/starrail/code/feed.xml".to_string(),
+            lang: "en-us".to_string(),
+            entries: vec![
+                FeedEntry {
+                    id: "urn:starrail-api:code:STARRAILGIFT".to_string(),
+                    title: "STARRAILGIFT".to_string(),
+                    link: "https://hsr.hoyoverse.com/gift?code=STARRAILGIFT&lang=en".to_string(),
+                    summary: "50 Stellar Jade <new>".to_string(),
+                    published: 1682467200,
+                    updated: 1760745600,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_rss() {
+        let xml = feed().to_rss();
+        assert!(xml.contains("<guid isPermaLink=\"false\">urn:starrail-api:code:STARRAILGIFT</guid>"));
+        assert!(xml.contains("<link>https://hsr.hoyoverse.com/gift?code=STARRAILGIFT&amp;lang=en</link>"));
+        assert!(xml.contains("<description>50 Stellar Jade &lt;new&gt;</description>"));
+        assert!(xml.contains("<pubDate>Wed, 26 Apr 2023 00:00:00 +0000</pubDate>"));
+        assert!(xml.contains("<lastBuildDate>Sat, 18 Oct 2025 00:00:00 +0000</lastBuildDate>"));
+    }
+
+    #[test]
+    fn test_atom() {
+        let xml = feed().to_atom();
+        assert!(xml.contains("<id>urn:starrail-api:code:STARRAILGIFT</id>"));
+        assert!(xml.contains("<published>2023-04-26T00:00:00Z</published>"));
+        assert!(xml.contains("<updated>2025-10-18T00:00:00Z</updated>"));
+        assert!(xml.contains("<subtitle>Redemption codes &amp; rewards</subtitle>"));
+    }
+}
@@ -0,0 +1,201 @@
+use log::warn;
+use reqwest::{Client, IntoUrl, Proxy, RequestBuilder, Response, StatusCode};
+use std::time::Duration;
+
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36";
+
+/// How an [`HttpClient`] talks to one upstream source.
+#[derive(Debug, Clone)]
+pub struct HttpOptions {
+    pub connect_timeout: Duration,
+    /// Limit on a whole request, including reading the body.
+    pub timeout: Duration,
+    /// Retries after the first attempt for timeouts, connection errors, 429 and 5xx responses.
+    pub retries: u32,
+    /// First retry delay, doubled for every further retry.
+    pub retry_delay: Duration,
+    /// Cap on a single retry delay, including one asked for by `Retry-After`.
+    pub max_retry_delay: Duration,
+    pub user_agent: String,
+    /// `http://`, `https://` or `socks5://` proxy URL.
+    pub proxy: Option<String>,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            timeout: Duration::from_secs(30),
+            retries: 2,
+            retry_delay: Duration::from_secs(1),
+            max_retry_delay: Duration::from_secs(60),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            proxy: None,
+        }
+    }
+}
+
+/// Upstream HTTP client shared by the resolvers and the validator. Requests sent through
+/// [`HttpClient::send`] are retried with jittered exponential backoff.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: Client,
+    options: HttpOptions,
+}
+
+impl HttpClient {
+    pub fn new(options: HttpOptions) -> Result<Self, reqwest::Error> {
+        let mut builder = Client::builder()
+            .connect_timeout(options.connect_timeout)
+            .timeout(options.timeout)
+            .user_agent(options.user_agent.as_str());
+        if let Some(proxy) = &options.proxy {
+            builder = builder.proxy(Proxy::all(proxy.as_str())?);
+        }
+
+        Ok(Self { client: builder.build()?, options })
+    }
+
+    pub fn get<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.client.get(url)
+    }
+
+    /// Sends `request`, retrying timeouts, connection errors, 429 and 5xx responses. The last
+    /// response or error is returned once the retries are used up.
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response, reqwest::Error> {
+        let mut attempt = 0;
+        loop {
+            // Streaming bodies cannot be replayed
+            let Some(current) = request.try_clone() else {
+                return request.send().await;
+            };
+
+            let delay = match current.send().await {
+                Ok(response) if attempt < self.options.retries && is_retryable(response.status()) => {
+                    let delay = retry_after(&response)
+                        .unwrap_or_else(|| self.backoff(attempt))
+                        .min(self.options.max_retry_delay);
+                    warn!("{} responded with {}, retrying in {:?}", response.url(), response.status(), delay);
+                    delay
+                },
+                Err(e) if attempt < self.options.retries && (e.is_timeout() || e.is_connect()) => {
+                    let delay = self.backoff(attempt).min(self.options.max_retry_delay);
+                    warn!("Request failed: {}, retrying in {:?}", e, delay);
+                    delay
+                },
+                result => return result,
+            };
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// `retry_delay * 2^attempt`, randomly shortened by up to half so that clients failing
+    /// together do not retry together.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self.options.retry_delay.saturating_mul(1 << attempt.min(16));
+        delay.mul_f64(rand::random_range(0.5..=1.0))
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new(HttpOptions::default()).expect("Default HTTP options are valid")
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// The delay asked for by a `Retry-After` header, in seconds or as an HTTP date.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (at.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().ok().or(Some(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use wiremock::matchers::{header, method, path};
+
+    fn client(retries: u32) -> HttpClient {
+        HttpClient::new(HttpOptions {
+            retries,
+            retry_delay: Duration::from_millis(10),
+            max_retry_delay: Duration::from_millis(50),
+            user_agent: "starrail-api-tests".to_string(),
+            ..Default::default()
+        }).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_retries_server_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/codes"))
+            .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "1"))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/codes"))
+            .and(header("User-Agent", "starrail-api-tests"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = client(2);
+        let response = client.send(client.get(format!("{}/codes", server.uri()))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+
+        let client = client(1);
+        let response = client.send(client.get(format!("{}/codes", server.uri()))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        // Client errors are returned straight away
+        let response = client.send(client.get(format!("{}/missing", server.uri()))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_backoff_jitter() {
+        let client = HttpClient::new(HttpOptions { retry_delay: Duration::from_secs(1), ..Default::default() }).unwrap();
+        for _ in 0..20 {
+            let delay = client.backoff(2);
+            assert!(delay >= Duration::from_secs(2) && delay <= Duration::from_secs(4));
+        }
+    }
+
+    #[test]
+    fn test_invalid_proxy() {
+        assert!(HttpClient::new(HttpOptions { proxy: Some("not a url".to_string()), ..Default::default() }).is_err());
+        assert!(HttpClient::new(HttpOptions { proxy: Some("socks5://127.0.0.1:1080".to_string()), ..Default::default() }).is_ok());
+    }
+}
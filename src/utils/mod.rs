@@ -1 +1,3 @@
-pub mod lang_parser; 
\ No newline at end of file
+pub mod lang_parser;
+pub mod feed;
+pub mod http;